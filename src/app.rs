@@ -1,19 +1,43 @@
 mod actions;
+mod byte_order;
+mod collisions;
+mod compare;
+mod controller_pack;
+mod data_dir;
+mod error;
 mod error_list;
 mod file_groups;
 mod help;
+mod hex_view;
+mod history;
+mod logging;
+mod merge_packs;
 mod options;
+mod output;
+mod overwrite;
+mod preflight;
+mod report;
+mod srm;
+mod srm_layout;
+mod sync;
+#[cfg(test)]
+mod test_dir;
+mod toasts;
+mod watch;
 mod work;
 
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, VecDeque};
 
-use crate::widgets::browser::{Browse, UiBrowser};
+use crate::widgets::{
+  browser::{Browse, UiBrowser},
+  trim_label::UiTrimLabel,
+};
 
 use self::{actions::Actions, file_groups::FileGroups, options::Options};
 
 use self::{
   error_list::ErrorList,
-  work::{start_worker_thread, ScanDirResult, Work, WorkResult},
+  work::{start_worker_thread, CheckPackResult, ScanDirResult, WatchEvent, Work, WorkResult},
 };
 
 pub(crate) mod shortcuts {
@@ -49,7 +73,19 @@ pub(crate) mod shortcuts {
 enum Windows {
   Options,
   Error,
-  ConversionEndMessage(Vec<String>),
+  ConversionEndMessage(file_groups::Converted, report::Report),
+  PackCheckResult(Vec<String>),
+  ByteOrderWarning(Vec<String>),
+  Collisions(collisions::Resolve),
+  ConfirmOverwrite(overwrite::Question),
+  Preflight(preflight::Report),
+  MergePacks(merge_packs::MergePacks),
+  SrmLayout(srm_layout::SrmLayout),
+  HexView(hex_view::HexView),
+  Compare(compare::Compare),
+  Sync(sync::Sync),
+  Watch,
+  History(history::History),
   InvalidEntries,
   Usage,
   About,
@@ -60,7 +96,19 @@ impl From<&Windows> for egui::WidgetText {
     match value {
       Windows::Options => Self::from("Conversion Options"),
       Windows::Error => Self::from("Could Not Complete"),
-      Windows::ConversionEndMessage(_) => Self::from("Conversion Successful"),
+      Windows::ConversionEndMessage(..) => Self::from("Conversion Successful"),
+      Windows::PackCheckResult(_) => Self::from("Controller Pack Check"),
+      Windows::ByteOrderWarning(_) => Self::from("Byte Order Mismatch"),
+      Windows::Collisions(_) => Self::from("Output Collisions"),
+      Windows::ConfirmOverwrite(_) => Self::from("File Already Exists"),
+      Windows::Preflight(_) => Self::from("Cannot Convert"),
+      Windows::MergePacks(_) => Self::from("Merge Controller Packs"),
+      Windows::SrmLayout(_) => Self::from("SRM Layout"),
+      Windows::HexView(_) => Self::from("Hex Viewer"),
+      Windows::Compare(_) => Self::from("Compare Saves"),
+      Windows::Sync(_) => Self::from("Sync Folders"),
+      Windows::Watch => Self::from("Watch Folders"),
+      Windows::History(_) => Self::from("Conversion History"),
       Windows::InvalidEntries => Self::from("Invalid Entries"),
       Windows::Usage => Self::from("Usage"),
       Windows::About => Self::from("About"),
@@ -76,6 +124,19 @@ pub struct RaMp64<'a> {
   worker: std::sync::mpsc::Sender<Work>,
   result_receiver: std::sync::mpsc::Receiver<WorkResult>,
   window_show_queue: VecDeque<Windows>,
+  watch: watch::Watch,
+  /// The groups of the last conversion, for the exported reports
+  last_runs: Vec<history::GroupRun>,
+  /// Routine results, which open their window when clicked
+  toasts: toasts::Toasts<Windows>,
+  /// Scans and pack checks sent to the worker, which did not finish yet
+  pending_jobs: usize,
+  /// The options as last logged, to log their changes
+  logged_options: history::RunOptions,
+  /// Saves changed while the user was busy with a window, converted once it closes
+  queued_changes: Vec<std::path::PathBuf>,
+  /// The log window, which stays open next to the others
+  log_view: Option<logging::LogView>,
 }
 
 impl<'a> RaMp64<'a> {
@@ -84,36 +145,388 @@ impl<'a> RaMp64<'a> {
     let (result_sender, result_receiver) = std::sync::mpsc::channel();
 
     start_worker_thread(receiver, result_sender);
+    logging::init();
 
+    let options = Options::default();
     Self {
       errors: ErrorList::default(),
       actions: Actions::default(),
-      options: Options::default(),
+      logged_options: history::RunOptions::new(&options),
+      options,
       file_groups: FileGroups::default(),
       worker,
       result_receiver,
       window_show_queue: VecDeque::new(),
+      watch: watch::Watch::default(),
+      last_runs: Vec::new(),
+      toasts: toasts::Toasts::default(),
+      pending_jobs: 0,
+      queued_changes: Vec::new(),
+      log_view: None,
+    }
+  }
+
+  /// Sends the job to the worker, counting it until its result comes back
+  fn send_work(&mut self, work: Work) {
+    match &work {
+      Work::ScanDirectory { dir, recursive } => {
+        log::info!("Scanning {} (recursive: {recursive})", dir.display())
+      }
+      Work::CheckPacks { files, repair } => {
+        log::info!(
+          "Checking {} controller packs (repair: {repair})",
+          files.len()
+        )
+      }
+      Work::Sync(_) | Work::DetectByteOrder(_) | Work::Watch { .. } => {}
+    }
+    if self.worker.send(work).is_ok() {
+      self.pending_jobs += 1;
     }
   }
 
   fn check_work_done(&mut self) {
     if let Ok(result) = self.result_receiver.try_recv() {
       match result {
-        WorkResult::ScanDirectory(scan_result) => self.check_scan_result(scan_result),
+        WorkResult::ScanDirectory(scan_result) => {
+          self.pending_jobs = self.pending_jobs.saturating_sub(1);
+          self.check_scan_result(scan_result)
+        }
+        WorkResult::CheckPacks(check_results) => {
+          self.pending_jobs = self.pending_jobs.saturating_sub(1);
+          self.check_pack_results(check_results)
+        }
+        WorkResult::Sync(done) => {
+          self.pending_jobs = self.pending_jobs.saturating_sub(1);
+          self.check_sync_result(done)
+        }
+        WorkResult::DetectByteOrder(detected) => {
+          self.pending_jobs = self.pending_jobs.saturating_sub(1);
+          self.file_groups.set_byte_orders(detected)
+        }
+        WorkResult::Watch(event) => self.check_watch_event(event),
+      }
+    }
+  }
+
+  /// Checks if a conversion waits for the user, or a window other than the watch one is open
+  fn is_busy(&self) -> bool {
+    self.file_groups.is_converting()
+      || self
+        .window_show_queue
+        .iter()
+        .any(|w| !matches!(w, Windows::Watch))
+  }
+
+  /// Converts the saves changed while the user was busy
+  fn convert_queued_changes(&mut self) {
+    if !self.queued_changes.is_empty() && !self.is_busy() {
+      let mut files = std::mem::take(&mut self.queued_changes);
+      files.sort();
+      files.dedup();
+      self.check_watch_event(WatchEvent::Changed(files));
+    }
+  }
+
+  fn check_sync_result(&mut self, done: sync::Done) {
+    let Some(Windows::Sync(folders)) = self
+      .window_show_queue
+      .iter_mut()
+      .find(|w| matches!(w, Windows::Sync(_)))
+    else {
+      return;
+    };
+    let errors = folders.finish(done);
+    if !errors.is_empty() {
+      for (category, error) in errors {
+        self.errors.add(category, error);
+      }
+      self.show_errors();
+    }
+  }
+
+  /// Logs the options once they changed, which is not done while they are being edited
+  fn log_option_changes(&mut self) {
+    if matches!(self.window_show_queue.front(), Some(Windows::Options)) {
+      return;
+    }
+    let options = history::RunOptions::new(&self.options);
+    if options != self.logged_options {
+      log::info!("Options changed: {options}");
+      self.logged_options = options;
+    }
+  }
+
+  /// Converts every group but the skipped ones, `check_existing` is off once the user has
+  /// resolved the collisions
+  fn convert(&mut self, skipped: Option<&BTreeSet<String>>, check_existing: bool) {
+    log::info!(
+      "Converting {} groups",
+      self
+        .file_groups
+        .len()
+        .saturating_sub(skipped.map_or(0, BTreeSet::len))
+    );
+    // errors kept from an earlier conversion are replaced by the ones of this one
+    let converted = |group: &String| {
+      self.file_groups.contains(group) && !skipped.is_some_and(|s| s.contains(group))
+    };
+    self
+      .errors
+      .retain(|error| !error.group.as_ref().is_some_and(converted));
+    let status = self
+      .file_groups
+      .convert(&self.options, &mut self.errors, skipped, check_existing);
+    self.conversion_status(status);
+  }
+
+  /// Appends the groups of a finished conversion to the history
+  fn record_history(&mut self, options: &Options, result: &file_groups::Converted) {
+    if result.runs.is_empty() {
+      return;
+    }
+    let run = history::Run::new(options, result.runs.clone());
+    if let Err(error) = history::append(&run) {
+      let error = error::AppError::from(error);
+      self
+        .errors
+        .add(ErrorCategory::of(&error, ErrorCategory::Output), error);
+    }
+  }
+
+  /// Acts on the group of an error, as asked from the error window
+  fn error_action(&mut self, action: error_list::ErrorAction) {
+    use error_list::ErrorAction::*;
+    match action {
+      Retry(group) => {
+        let skipped = self.file_groups.others(&[group].into());
+        self.convert(Some(&skipped), true);
+      }
+      RetryAll => {
+        let groups = self.errors.groups(|g| self.file_groups.contains(g));
+        self.convert(Some(&self.file_groups.others(&groups)), true);
+      }
+      ShowGroup(group) => {
+        // the errors are kept, and shown again with the next conversion
+        self.file_groups.select(&group);
+        self
+          .window_show_queue
+          .retain(|w| !matches!(w, Windows::Error));
+      }
+      RemoveGroup(group) => {
+        self.file_groups.remove(&group);
+        self
+          .errors
+          .retain(|error| error.group.as_ref() != Some(&group));
+      }
+    }
+    if !self.errors.has_errors() {
+      self
+        .window_show_queue
+        .retain(|w| !matches!(w, Windows::Error));
+    }
+  }
+
+  /// Closes the front window, dropping the errors it showed or the conversion it was waiting on
+  fn close_window(&mut self) -> Option<Windows> {
+    let window = self.window_show_queue.pop_front();
+    match window {
+      Some(Windows::Error) => self.errors.clear(),
+      Some(Windows::ConfirmOverwrite(_)) => self.file_groups.cancel_conversion(),
+      _ => {}
+    }
+    window
+  }
+
+  /// Queues the error window, unless there are no errors or it is already queued
+  fn show_errors(&mut self) {
+    if self.errors.has_errors()
+      && !self
+        .window_show_queue
+        .iter()
+        .any(|w| matches!(w, Windows::Error))
+    {
+      self.window_show_queue.push_back(Windows::Error)
+    }
+  }
+
+  fn conversion_status(&mut self, status: file_groups::ConvertStatus) {
+    let success = match status {
+      file_groups::ConvertStatus::Problems(problems) => {
+        for problem in &problems {
+          log::warn!("Cannot convert: {problem}");
+        }
+        self
+          .window_show_queue
+          .push_back(Windows::Preflight(preflight::Report::new(problems)));
+        return;
+      }
+      file_groups::ConvertStatus::Collisions(collisions) => {
+        log::warn!("Conversion stopped: {} outputs collide", collisions.len());
+        self
+          .window_show_queue
+          .push_back(Windows::Collisions(collisions::Resolve::new(collisions)));
+        return;
+      }
+      file_groups::ConvertStatus::Ask(question) => {
+        self
+          .window_show_queue
+          .push_front(Windows::ConfirmOverwrite(question));
+        return;
+      }
+      file_groups::ConvertStatus::Done(success) => success,
+    };
+    for run in &success.runs {
+      log::info!("{}: {}", run.group, run.outcome.name());
+    }
+    self.record_history(&self.options.clone(), &success);
+    self.last_runs = success.runs.clone();
+    let report = report::Report::new(&self.last_runs, &self.errors);
+    self.show_errors();
+    if !success.is_empty() {
+      let mut message = format!("{} groups converted", success.converted.len());
+      if !success.unchanged.is_empty() {
+        message.push_str(&format!(", {} unchanged", success.unchanged.len()));
+      }
+      self.toasts.info(
+        message,
+        Some(Windows::ConversionEndMessage(success, report)),
+      );
+    }
+  }
+
+  fn check_pack_results(&mut self, check_results: Vec<CheckPackResult>) {
+    let had_errors = self.errors.len();
+    let mut checked = Vec::with_capacity(check_results.len());
+    for result in check_results {
+      match result {
+        Ok(file) => {
+          log::info!("Checked {file}");
+          checked.push(file.to_string());
+          for issue in file.issues {
+            self.errors.add(ErrorCategory::PackIntegrity, issue);
+          }
+        }
+        Err(error) => self.errors.add(ErrorCategory::PackIntegrity, error),
+      }
+    }
+    let problems = self.errors.len() - had_errors;
+    if problems > 0 {
+      self.toasts.error(
+        format!("{problems} problems found in controller packs"),
+        Some(Windows::Error),
+      );
+    }
+    if !checked.is_empty() {
+      self.toasts.info(
+        format!("{} controller packs checked", checked.len()),
+        Some(Windows::PackCheckResult(checked)),
+      );
+    }
+  }
+
+  fn check_watch_event(&mut self, event: WatchEvent) {
+    match event {
+      WatchEvent::Started(dirs) => {
+        self.watch.set_active(true);
+        self.watch.log(format!("Watching {} folders", dirs.len()));
+      }
+      WatchEvent::Stopped => {
+        self.watch.set_active(false);
+        self.watch.log("Stopped watching");
+      }
+      WatchEvent::Failed(error) => {
+        self.watch.set_active(false);
+        self.watch.log_error(format!("Stopped watching: {error}"));
+      }
+      WatchEvent::Overflowed => {
+        self
+          .watch
+          .log_error("Too many changes at once, converting every save of the watched folders");
+      }
+      WatchEvent::Changed(files) => {
+        for file in &files {
+          self.watch.log(format!("Changed {}", file.display()));
+        }
+        // converting now would drop a conversion waiting for an answer
+        if self.is_busy() {
+          self.watch.log("Waiting for the open windows to close");
+          self.queued_changes.extend(files);
+          return;
+        }
+        let skipped = self.file_groups.add_changed_files(files);
+        // outputs of earlier runs are replaced, unless nothing changed in them or they were
+        // written since, like the saves of a standalone emulator
+        let options = Options {
+          user_params: ramp64_srm_convert_lib::UserParams {
+            overwrite: true,
+            ..self.options.user_params
+          },
+          ask_overwrite: false,
+          skip_identical: true,
+          skip_newer: true,
+          ..self.options.clone()
+        };
+        let had_errors = self.errors.has_errors();
+        match self
+          .file_groups
+          .convert(&options, &mut self.errors, Some(&skipped), true)
+        {
+          file_groups::ConvertStatus::Done(result) => {
+            self.record_history(&options, &result);
+            for run in &result.runs {
+              if run.outcome == history::Outcome::Skipped {
+                self
+                  .watch
+                  .log_error(format!("Kept {}, its outputs are newer", run.group));
+              }
+            }
+            self.last_runs = result.runs;
+            for group in result.converted {
+              self.watch.log(format!("Converted {group}"));
+            }
+            for group in result.unchanged {
+              self.watch.log(format!("Unchanged {group}"));
+            }
+          }
+          status => self.conversion_status(status),
+        }
+        if !had_errors && self.errors.has_errors() {
+          self.watch.log_error("Some groups could not be converted");
+          self.show_errors();
+        }
       }
     }
   }
 
   fn check_scan_result(&mut self, scan_result: ScanDirResult) {
     match scan_result {
-      Ok(files) => {
-        self.file_groups.add_files(files);
+      Ok(scanned) => {
+        log::info!(
+          "Scanned {}: {} files",
+          scanned.root.display(),
+          scanned.files.len()
+        );
+        self.toasts.info(
+          format!(
+            "{} files added from {}",
+            scanned.files.len(),
+            scanned.root.display()
+          ),
+          None,
+        );
+        self
+          .file_groups
+          .add_scanned_files(scanned.root, scanned.files);
       }
       Err(error) => {
-        if !self.errors.has_errors() {
-          self.window_show_queue.push_back(Windows::Error);
-        }
-        self.errors.add(ErrorCategory::AddFile, error)
+        self.toasts.error(
+          format!("Scan failed for {}", error.path.display()),
+          Some(Windows::Error),
+        );
+        self
+          .errors
+          .add(ErrorCategory::Scan, error::AppError::scan(error))
       }
     }
   }
@@ -121,30 +534,105 @@ impl<'a> RaMp64<'a> {
   fn enabled(&self) -> bool {
     self.window_show_queue.is_empty()
   }
+
+  /// Shows the counts of the groups, the worker jobs and where outputs go
+  fn show_status(&mut self, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+      let (groups, valid) = (self.file_groups.len(), self.file_groups.valid_count());
+      ui.label(format!("{groups} groups"));
+      ui.separator();
+      ui.label(format!("{valid} valid"));
+      ui.separator();
+      if groups == valid {
+        ui.label("0 invalid");
+      } else if ui
+        .link(
+          egui::RichText::new(format!("{} invalid", groups - valid))
+            .color(ui.visuals().error_fg_color),
+        )
+        .on_hover_text("Show the invalid entries")
+        .clicked()
+      {
+        self.window_show_queue.push_back(Windows::InvalidEntries);
+      }
+      ui.separator();
+      ui.label(format!("{} selected", self.file_groups.selected_count()));
+      ui.separator();
+      if self.pending_jobs > 0 {
+        ui.spinner();
+        ui.label(format!("{} jobs pending", self.pending_jobs));
+        // the results are only picked up while repainting
+        ui.ctx()
+          .request_repaint_after(std::time::Duration::from_millis(100));
+      } else {
+        ui.weak("Idle");
+      }
+      ui.separator();
+      ui.label("Output");
+      match &self.options.output_dir {
+        Some(dir) => ui.trim_label(dir.to_string_lossy(), false),
+        None => ui.weak("Next to the inputs"),
+      };
+    });
+  }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub(crate) enum ErrorCategory {
   AddFile,
+  Scan,
+  Validation,
   Conversion,
+  Output,
+  Permission,
+  PackIntegrity,
+}
+
+impl ErrorCategory {
+  /// Picks the category from the kind of the error, or uses the given one
+  pub(crate) fn of(error: &error::AppError, default: Self) -> Self {
+    use error::ErrorKind;
+    match &error.kind {
+      ErrorKind::Scan(_) => ErrorCategory::Scan,
+      ErrorKind::Validation(_) => ErrorCategory::Validation,
+      ErrorKind::Permission(_) => ErrorCategory::Permission,
+      ErrorKind::Io(e) if e.kind() == std::io::ErrorKind::AlreadyExists => ErrorCategory::Output,
+      _ => default,
+    }
+  }
 }
 
 impl error_list::Category for ErrorCategory {
   fn name(&self) -> &str {
     match self {
       ErrorCategory::AddFile => "Add File",
+      ErrorCategory::Scan => "Scan",
+      ErrorCategory::Validation => "Validation",
       ErrorCategory::Conversion => "Conversion",
+      ErrorCategory::Output => "Output",
+      ErrorCategory::Permission => "Permission",
+      ErrorCategory::PackIntegrity => "Controller Pack",
     }
   }
 
   fn description(&self) -> &str {
     match self {
       ErrorCategory::AddFile => "All files which could not be added",
+      ErrorCategory::Scan => "All folders which could not be scanned",
+      ErrorCategory::Validation => "All groups missing files or with files which do not match",
       ErrorCategory::Conversion => "All groups which could not be converted",
+      ErrorCategory::Output => "All output files which could not be written",
+      ErrorCategory::Permission => "All files which could not be accessed",
+      ErrorCategory::PackIntegrity => "All problems found in controller packs",
     }
   }
 }
 
+fn export_report() -> Browse<'static> {
+  Browse::save_file(Some("conversion-report.csv"), &report::REPORT_FILTERS)
+    .set_default_text("Export Report…")
+}
+
 fn check_can_add_file(path: &std::path::Path) -> bool {
   use std::ffi::OsStr;
   [
@@ -164,6 +652,8 @@ fn check_can_add_file(path: &std::path::Path) -> bool {
 impl<'a> eframe::App for RaMp64<'a> {
   fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
     self.check_work_done();
+    self.convert_queued_changes();
+    self.log_option_changes();
 
     let enabled = self.enabled();
 
@@ -190,10 +680,20 @@ impl<'a> eframe::App for RaMp64<'a> {
         AddFile(selected_file) => {
           if check_can_add_file(&selected_file) {
             self.file_groups.add_file(selected_file);
+          } else {
+            self.toasts.error(
+              format!("{} is not a supported save file", selected_file.display()),
+              Some(Windows::Error),
+            );
+            let error = error::AppError::format("not a supported save file").at(selected_file);
+            self.errors.add(ErrorCategory::AddFile, error);
           }
         }
         AddDir(selected_dir) => {
-          let _ = self.worker.send(Work::ScanDirectory(selected_dir));
+          self.send_work(Work::ScanDirectory {
+            dir: selected_dir,
+            recursive: self.options.recursive_scan,
+          });
         }
         SelectAll => self.file_groups.select_all(),
         RemoveAll => self.file_groups.clear(),
@@ -201,15 +701,50 @@ impl<'a> eframe::App for RaMp64<'a> {
         Quit => frame.close(),
         Usage => self.window_show_queue.push_back(Windows::Usage),
         Escape => {
-          if self.window_show_queue.pop_front().is_none() {
+          if self.close_window().is_none() {
             frame.close();
           }
         }
         ShowInvalid => self.window_show_queue.push_back(Windows::InvalidEntries),
+        MergePacks => self
+          .window_show_queue
+          .push_back(Windows::MergePacks(Default::default())),
+        CompareSaves => self
+          .window_show_queue
+          .push_back(Windows::Compare(Default::default())),
+        SyncFolders => self
+          .window_show_queue
+          .push_back(Windows::Sync(Default::default())),
+        WatchFolders => self.window_show_queue.push_back(Windows::Watch),
+        ShowHistory => self
+          .window_show_queue
+          .push_back(Windows::History(history::History::load())),
+        ShowLog => {
+          self.log_view.get_or_insert_with(Default::default);
+        }
+        CheckPacks | RepairPacks => {
+          self.send_work(Work::CheckPacks {
+            files: self.file_groups.pack_files(),
+            repair: matches!(action, RepairPacks),
+          });
+        }
         About => self.window_show_queue.push_back(Windows::About),
       }
     }
 
+    egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| self.show_status(ui));
+
+    if self.watch.has_activity() {
+      egui::TopBottomPanel::bottom("activity_log")
+        .resizable(true)
+        .default_height(120.0)
+        .show(ctx, |ui| self.watch.show_log(ui));
+    }
+    if self.watch.is_active() {
+      // picks up changes while the window is idle
+      ctx.request_repaint_after(watch::DEBOUNCE);
+    }
+
     egui::CentralPanel::default().show(ctx, |ui| {
       ui.set_enabled(enabled);
 
@@ -230,6 +765,16 @@ impl<'a> eframe::App for RaMp64<'a> {
       ui.allocate_rect(items_max_rect, egui::Sense::hover());
 
       self.file_groups.show(&mut items_ui);
+      if let Some(action) = self.file_groups.get_last_action() {
+        match action {
+          file_groups::ItemAction::ShowLayout(path) => self
+            .window_show_queue
+            .push_back(Windows::SrmLayout(srm_layout::SrmLayout::new(path))),
+          file_groups::ItemAction::ShowHex(path) => self
+            .window_show_queue
+            .push_back(Windows::HexView(hex_view::HexView::new(path))),
+        }
+      }
 
       ui.horizontal(|ui| {
         ui.label("Output Directory");
@@ -241,25 +786,54 @@ impl<'a> eframe::App for RaMp64<'a> {
       ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
         ui.add_enabled_ui(self.file_groups.are_all_valid(), |ui| {
           if ui.button("Convert").clicked() {
-            let had_errors = self.errors.has_errors();
-            let success = self.file_groups.convert(&self.options, &mut self.errors);
-            if !had_errors && self.errors.has_errors() {
-              self.window_show_queue.push_back(Windows::Error)
-            }
-            if !success.is_empty() {
+            self.file_groups.detect_byte_orders();
+            let conflicts = self.file_groups.byte_order_conflicts(&self.options);
+            if conflicts.is_empty() {
+              self.convert(None, true);
+            } else {
               self
                 .window_show_queue
-                .push_back(Windows::ConversionEndMessage(success));
+                .push_back(Windows::ByteOrderWarning(conflicts));
             }
           }
         });
       });
     });
 
+    match self.toasts.show(ctx) {
+      Some(Windows::Error) => self.show_errors(),
+      Some(window) => self.window_show_queue.push_back(window),
+      None => {}
+    }
+
+    if let Some(log_view) = &mut self.log_view {
+      let mut showing = true;
+      egui::Window::new("Log")
+        .collapsible(false)
+        .default_size(ctx.screen_rect().size() * 0.6)
+        .open(&mut showing)
+        .show(ctx, |ui| log_view.show(ui));
+      if !showing {
+        self.log_view = None;
+      }
+    }
+
     if !self.window_show_queue.is_empty() {
       let mut showing = true;
-      let window = self.window_show_queue.front().unwrap();
-      egui::Window::new(window)
+      let mut convert = false;
+      let mut resolved = None;
+      let mut answered = None;
+      let mut sync_job = None;
+      let mut export = None;
+      let mut error_action = None;
+      let window = self.window_show_queue.front_mut().unwrap();
+      let title = match window {
+        Windows::Error => format!("Could Not Complete ({})", self.errors.len()).into(),
+        _ => egui::WidgetText::from(&*window),
+      };
+      egui::Window::new(title)
+        // the title of the error window changes with the count
+        .id(egui::Id::new(egui::WidgetText::from(&*window).text()))
         .anchor(egui::Align2::CENTER_CENTER, [0.0, -10.0])
         .collapsible(false)
         .default_size(ctx.screen_rect().size() * 0.85)
@@ -273,7 +847,15 @@ impl<'a> eframe::App for RaMp64<'a> {
         .show(ctx, |ui| match window {
           Windows::Usage => help::Usage::default().show(ui),
           Windows::Options => self.options.show(ui),
-          Windows::Error => self.errors.show(ui),
+          Windows::Error => {
+            let file_groups = &self.file_groups;
+            error_action = self.errors.show(ui, |group| file_groups.contains(group));
+            ui.add_space(6.0);
+            let mut path = None;
+            if ui.browse(&mut path, export_report()).changed() {
+              export = path.map(|p| (report::Report::new(&self.last_runs, &self.errors), p));
+            }
+          }
           Windows::InvalidEntries => {
             ui.label("The following entries are missing files:");
             ui.add_space(3.0);
@@ -287,13 +869,19 @@ impl<'a> eframe::App for RaMp64<'a> {
               });
             });
           }
-          Windows::ConversionEndMessage(entries) => {
-            if self.file_groups.is_empty() {
+          Windows::ConversionEndMessage(result, report) => {
+            if self.file_groups.is_empty() && result.unchanged.is_empty() {
               ui.label(format!("All files where converted successfully!",));
             } else {
               ui.label(format!(
                 "{} files where converted successfully.",
-                entries.len()
+                result.converted.len()
+              ));
+            }
+            if !result.unchanged.is_empty() {
+              ui.label(format!(
+                "{} files were unchanged, as their outputs already existed.",
+                result.unchanged.len()
               ));
             }
 
@@ -305,12 +893,76 @@ impl<'a> eframe::App for RaMp64<'a> {
             ui.scope(|ui| {
               ui.visuals_mut().indent_has_left_vline = false;
               ui.indent("success_entries", |ui| {
-                for entry in entries {
+                for entry in result.converted.iter() {
+                  ui.label(entry);
+                }
+              });
+              if !result.unchanged.is_empty() {
+                ui.add_space(3.0);
+                ui.strong("Unchanged");
+                ui.indent("unchanged_entries", |ui| {
+                  for entry in result.unchanged.iter() {
+                    ui.label(entry);
+                  }
+                });
+              }
+            });
+            ui.add_space(6.0);
+            let mut path = None;
+            if ui.browse(&mut path, export_report()).changed() {
+              export = path.map(|p| (report.clone(), p));
+            }
+          }
+          Windows::PackCheckResult(entries) => {
+            ui.label("The following files were checked:");
+            ui.scope(|ui| {
+              ui.visuals_mut().indent_has_left_vline = false;
+              ui.indent("checked_entries", |ui| {
+                for entry in entries.iter() {
                   ui.label(entry);
                 }
               });
             });
           }
+          Windows::ByteOrderWarning(conflicts) => {
+            ui.label("The detected byte order of the EEP/FLA files in this batch disagree:");
+            ui.scope(|ui| {
+              ui.visuals_mut().indent_has_left_vline = false;
+              ui.indent("byte_order_conflicts", |ui| {
+                for conflict in conflicts.iter() {
+                  ui.label(conflict);
+                }
+              });
+            });
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+              if ui.button("Use Detected Order").clicked() {
+                self.file_groups.apply_detected_byte_order();
+                convert = true;
+              }
+              if ui.button("Convert Anyway").clicked() {
+                convert = true;
+              }
+            });
+          }
+          Windows::Collisions(resolve) => resolved = resolve.show(ui),
+          Windows::ConfirmOverwrite(question) => answered = question.show(ui),
+          Windows::Preflight(report) => report.show(ui),
+          Windows::MergePacks(merge) => {
+            if let Some(path) = merge.show(ui) {
+              self.file_groups.add_file(path);
+            }
+          }
+          Windows::SrmLayout(layout) => layout.show(ui, self.options.output_mupen),
+          Windows::HexView(view) => view.show(ui),
+          Windows::Compare(compare) => compare.show(ui),
+          Windows::Sync(folders) => sync_job = folders.show(ui, &self.options),
+          Windows::History(history) => history.show(ui),
+          Windows::Watch => {
+            if let Some(dirs) = self.watch.show(ui) {
+              let _ = self.worker.send(Work::Watch { dirs });
+            }
+          }
           Windows::About => {
             ui.heading("RaMp64 Converter GUI");
             ui.label("A minimal Rust-powered application built using egui.");
@@ -328,12 +980,45 @@ impl<'a> eframe::App for RaMp64<'a> {
             ui.hyperlink_to("Rust home", "https://www.rust-lang.org");
           }
         });
-      if !showing {
-        if matches!(window, Windows::Error) {
-          self.errors.clear();
-        }
+      if answered.is_some() {
         self.window_show_queue.pop_front();
+      } else if !showing || convert || resolved.is_some() {
+        self.close_window();
       }
+      if convert {
+        self.convert(None, true);
+      }
+      if let Some(skipped) = resolved {
+        self.convert(Some(&skipped), false);
+      }
+      if let Some(job) = sync_job {
+        self.send_work(Work::Sync(job));
+      }
+      if let Some(action) = error_action {
+        self.error_action(action);
+      }
+      if let Some((report, path)) = export {
+        if let Err(error) = report.export(&path) {
+          let error = error::AppError::from(error);
+          self
+            .errors
+            .add(ErrorCategory::of(&error, ErrorCategory::Output), error);
+          self.show_errors();
+        }
+      }
+      if let Some((decision, apply_to_all)) = answered {
+        let status = self
+          .file_groups
+          .answer(decision, apply_to_all, &mut self.errors);
+        self.conversion_status(status);
+      }
+    }
+
+    // the files added this frame are read on the worker
+    let probes = self.file_groups.take_byte_order_probes();
+    if !probes.is_empty() {
+      self.send_work(Work::DetectByteOrder(probes));
+      ctx.request_repaint();
     }
   }
 }
@@ -16,6 +16,14 @@ pub(crate) enum Action {
   Usage,
   Escape,
   ShowInvalid,
+  CheckPacks,
+  RepairPacks,
+  MergePacks,
+  CompareSaves,
+  SyncFolders,
+  WatchFolders,
+  ShowHistory,
+  ShowLog,
   About,
 }
 
@@ -159,9 +167,37 @@ impl<'f> Actions<'f> {
           if ui.button("Show Invalid").clicked() {
             self.set_action(Action::ShowInvalid, ui);
           }
+
+          ui.separator();
+
+          if ui.button("Check Controller Packs").clicked() {
+            self.set_action(Action::CheckPacks, ui);
+          }
+          if ui.button("Repair Controller Packs").clicked() {
+            self.set_action(Action::RepairPacks, ui);
+          }
         });
       });
 
+      ui.menu_button("Tools", |ui| {
+        if ui.button("Merge Controller Packs").clicked() {
+          self.set_action(Action::MergePacks, ui);
+        }
+        if ui.button("Compare Saves").clicked() {
+          self.set_action(Action::CompareSaves, ui);
+        }
+        if ui.button("Sync Folders").clicked() {
+          self.set_action(Action::SyncFolders, ui);
+        }
+        if ui.button("Watch Folders").clicked() {
+          self.set_action(Action::WatchFolders, ui);
+        }
+        ui.separator();
+        if ui.button("Conversion History").clicked() {
+          self.set_action(Action::ShowHistory, ui);
+        }
+      });
+
       ui.menu_button("Help", |ui| {
         if ui.button("Show Usage").clicked() {
           self.set_action(Action::Usage, ui);
@@ -169,6 +205,9 @@ impl<'f> Actions<'f> {
         if ui.button("View Wiki Help").clicked() {
           open::that_in_background("https://github.com/drehren/ramp64-convert-gui/wiki/Help");
         }
+        if ui.button("Show Log").clicked() {
+          self.set_action(Action::ShowLog, ui);
+        }
         ui.separator();
         if ui.button("About").clicked() {
           self.set_action(Action::About, ui);
@@ -0,0 +1,164 @@
+use super::srm::{self, Region};
+
+/// Minimum letter case score for the text heuristic to decide an order
+const MIN_TEXT_SCORE: i64 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ByteOrder {
+  /// Same order as the data stored in the SRM
+  Srm,
+  /// Every 32 bit word is byte-swapped
+  Swapped,
+}
+
+impl std::fmt::Display for ByteOrder {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ByteOrder::Srm => f.write_str("Same as SRM"),
+      ByteOrder::Swapped => f.write_str("Word-swapped"),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Basis {
+  /// The data matched the group SRM in this order
+  SrmMatch,
+  /// Text found in the data reads correctly in this order
+  Text,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Detection {
+  pub order: ByteOrder,
+  pub basis: Basis,
+}
+
+impl Detection {
+  pub fn swap_bytes(&self) -> bool {
+    self.order == ByteOrder::Swapped
+  }
+}
+
+impl std::fmt::Display for Detection {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.basis {
+      Basis::SrmMatch => f.write_fmt(format_args!("{} (matches SRM)", self.order)),
+      Basis::Text => f.write_fmt(format_args!("{} (from text)", self.order)),
+    }
+  }
+}
+
+fn compare_with_srm(data: &[u8], region: &[u8]) -> Option<ByteOrder> {
+  let len = data.len().min(region.len()) & !3;
+  if len == 0 {
+    return None;
+  }
+  let (data, region) = (&data[..len], &region[..len]);
+  let mut swapped = data.to_vec();
+  srm::word_swap(&mut swapped);
+
+  let same = data.iter().zip(region).filter(|(a, b)| a == b).count();
+  let swapped = swapped.iter().zip(region).filter(|(a, b)| a == b).count();
+  let best = same.max(swapped);
+  // identical scores mean the order does not matter, like in empty data
+  if same == swapped || best * 2 < len {
+    None
+  } else if same > swapped {
+    Some(ByteOrder::Srm)
+  } else {
+    Some(ByteOrder::Swapped)
+  }
+}
+
+/// Scores how much the letters in the data look like words written in order
+fn text_score(data: &[u8]) -> i64 {
+  data
+    .windows(2)
+    .map(|pair| match (pair[0], pair[1]) {
+      (a, b) if a.is_ascii_uppercase() && b.is_ascii_lowercase() => 1,
+      (a, b) if a.is_ascii_lowercase() && b.is_ascii_uppercase() => -1,
+      _ => 0,
+    })
+    .sum()
+}
+
+/// Detects the byte order of a native EEPROM or FlashRAM save
+pub(crate) fn detect(data: &[u8], srm_region: Option<&[u8]>) -> Option<Detection> {
+  if let Some(order) = srm_region.and_then(|region| compare_with_srm(data, region)) {
+    return Some(Detection {
+      order,
+      basis: Basis::SrmMatch,
+    });
+  }
+
+  let score = text_score(data);
+  let order = if score >= MIN_TEXT_SCORE {
+    ByteOrder::Srm
+  } else if score <= -MIN_TEXT_SCORE {
+    ByteOrder::Swapped
+  } else {
+    return None;
+  };
+  Some(Detection {
+    order,
+    basis: Basis::Text,
+  })
+}
+
+/// The files a group detects its byte order from
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Probe {
+  pub path: std::path::PathBuf,
+  pub region: Region,
+  pub srm: Option<std::path::PathBuf>,
+}
+
+impl Probe {
+  pub fn detect(&self) -> Option<Detection> {
+    detect_file(&self.path, self.region, self.srm.as_deref())
+  }
+}
+
+/// Detects the byte order of the save file, comparing it with the SRM file if given
+pub(crate) fn detect_file(
+  path: &std::path::Path,
+  region: Region,
+  srm: Option<&std::path::Path>,
+) -> Option<Detection> {
+  let data = std::fs::read(path).ok()?;
+  let srm_data = srm.and_then(|srm| srm::read(srm).ok());
+  detect(&data, srm_data.as_ref().map(|d| &d[region.range()]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detect_from_srm() {
+    let region = (0..64u8).collect::<Vec<_>>();
+    let mut swapped = region.clone();
+    srm::word_swap(&mut swapped);
+
+    let detection = detect(&region, Some(&region)).unwrap();
+    assert_eq!(detection.order, ByteOrder::Srm);
+    assert_eq!(detection.basis, Basis::SrmMatch);
+    assert_eq!(
+      detect(&swapped, Some(&region)).map(|d| d.order),
+      Some(ByteOrder::Swapped)
+    );
+    // empty data can not be told apart
+    assert_eq!(detect(&[0xff; 64], Some(&[0xff; 64])), None);
+  }
+
+  #[test]
+  fn detect_from_text() {
+    let mut data = b"Link    Zelda   Navi    Epona   ".to_vec();
+    assert_eq!(detect(&data, None).map(|d| d.order), Some(ByteOrder::Srm));
+    srm::word_swap(&mut data);
+    let detection = detect(&data, None).unwrap();
+    assert_eq!(detection.order, ByteOrder::Swapped);
+    assert_eq!(detection.basis, Basis::Text);
+  }
+}
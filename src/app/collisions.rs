@@ -0,0 +1,109 @@
+use crate::widgets::trim_label::UiTrimLabel;
+
+use std::collections::BTreeSet;
+
+/// Why an output file can not be written as is
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Kind {
+  /// More than one group writes the file
+  Shared,
+  /// The file exists, and the groups writing it do not overwrite
+  Exists,
+}
+
+impl Kind {
+  fn title(&self) -> &str {
+    match self {
+      Kind::Shared => "Written by more than one group",
+      Kind::Exists => "Already exist, and overwriting is off",
+    }
+  }
+}
+
+/// An output file which more than one group writes, or which already exists
+#[derive(Debug)]
+pub(crate) struct Collision {
+  pub target: std::path::PathBuf,
+  pub groups: Vec<String>,
+  pub kind: Kind,
+}
+
+/// Dialog to pick which groups are converted when their outputs collide
+#[derive(Debug)]
+pub(crate) struct Resolve {
+  collisions: Vec<Collision>,
+  skipped: BTreeSet<String>,
+}
+
+impl Resolve {
+  pub fn new(collisions: Vec<Collision>) -> Self {
+    Self {
+      collisions,
+      skipped: BTreeSet::new(),
+    }
+  }
+
+  /// Shows the collisions, returning the groups to skip once the user chooses to convert
+  pub fn show(&mut self, ui: &mut egui::Ui) -> Option<BTreeSet<String>> {
+    ui.label("Some output files collide. Uncheck the groups which should not be converted:");
+    ui.add_space(3.0);
+
+    for kind in [Kind::Shared, Kind::Exists] {
+      if !self.collisions.iter().any(|c| c.kind == kind) {
+        continue;
+      }
+      ui.strong(kind.title());
+      for (i, collision) in self.collisions.iter().enumerate() {
+        if collision.kind != kind {
+          continue;
+        }
+        ui.push_id(i, |ui| {
+          ui.trim_label(collision.target.to_string_lossy(), false);
+          ui.indent("collision_groups", |ui| {
+            for group in &collision.groups {
+              let mut convert = !self.skipped.contains(group);
+              if ui.checkbox(&mut convert, group).changed() {
+                if convert {
+                  self.skipped.remove(group);
+                } else {
+                  self.skipped.insert(group.clone());
+                }
+              }
+            }
+          });
+        });
+      }
+      ui.add_space(3.0);
+    }
+
+    ui.add_space(6.0);
+    let mut result = None;
+    ui.horizontal(|ui| {
+      if ui.button("Skip All Colliding Groups").clicked() {
+        self.skipped.extend(
+          self
+            .collisions
+            .iter()
+            .flat_map(|c| c.groups.iter().cloned()),
+        );
+      }
+      let shared = self.collisions.iter().any(|c| {
+        c.kind == Kind::Shared
+          && c
+            .groups
+            .iter()
+            .filter(|g| !self.skipped.contains(*g))
+            .count()
+            > 1
+      });
+      if ui
+        .add_enabled(!shared, egui::Button::new("Convert"))
+        .on_disabled_hover_text("Some outputs are still written by more than one group")
+        .clicked()
+      {
+        result = Some(self.skipped.clone());
+      }
+    });
+    result
+  }
+}
@@ -0,0 +1,266 @@
+use super::{
+  byte_order::ByteOrder,
+  srm::{self, Region},
+  work::GenericError,
+};
+
+use crate::widgets::browser::{Browse, FileFilter, UiBrowser};
+
+use std::ops::Range;
+
+const SAVE_FILTERS: [FileFilter; 1] = [FileFilter::new(
+  "Save Files",
+  &[
+    "srm", "eep", "sra", "fla", "mpk", "mpk1", "mpk2", "mpk3", "mpk4",
+  ],
+)];
+
+#[derive(Debug, PartialEq)]
+struct Diff {
+  /// Ranges of bytes which differ, within the length both sides share
+  ranges: Vec<Range<usize>>,
+  changed_bytes: usize,
+  lengths: (usize, usize),
+}
+
+fn diff(left: &[u8], right: &[u8]) -> Diff {
+  let mut ranges: Vec<Range<usize>> = Vec::new();
+  let mut changed_bytes = 0;
+  for (i, _) in left
+    .iter()
+    .zip(right)
+    .enumerate()
+    .filter(|(_, (l, r))| l != r)
+  {
+    changed_bytes += 1;
+    match ranges.last_mut() {
+      Some(range) if range.end == i => range.end += 1,
+      _ => ranges.push(i..i + 1),
+    }
+  }
+  Diff {
+    ranges,
+    changed_bytes,
+    lengths: (left.len(), right.len()),
+  }
+}
+
+/// Compares the data in both byte orders, keeping the order with the fewest changed bytes
+fn diff_orders(left: &[u8], right: &[u8]) -> (Diff, ByteOrder) {
+  let same = diff(left, right);
+  let mut swapped_right = right.to_vec();
+  srm::word_swap(&mut swapped_right);
+  let swapped = diff(left, &swapped_right);
+  if swapped.changed_bytes < same.changed_bytes {
+    (swapped, ByteOrder::Swapped)
+  } else {
+    (same, ByteOrder::Srm)
+  }
+}
+
+#[derive(Debug, Default)]
+struct Side {
+  path: Option<std::path::PathBuf>,
+  /// Region of the SRM to compare, or the whole file if `None`
+  region: Option<Region>,
+  swap_bytes: bool,
+}
+
+impl Side {
+  fn is_srm(&self) -> bool {
+    self
+      .path
+      .as_ref()
+      .and_then(|p| p.extension())
+      .is_some_and(|e| e.eq_ignore_ascii_case("srm"))
+  }
+
+  /// Checks if the side holds EEPROM or FlashRAM data, which may be word-swapped
+  fn is_battery(&self) -> bool {
+    if self.is_srm() {
+      return matches!(self.region, Some(Region::Eeprom | Region::FlashRam));
+    }
+    self
+      .path
+      .as_ref()
+      .and_then(|p| p.extension())
+      .is_some_and(|e| e.eq_ignore_ascii_case("eep") || e.eq_ignore_ascii_case("fla"))
+  }
+
+  fn load(&self, path: &std::path::Path) -> Result<Vec<u8>, GenericError> {
+    let data = if self.is_srm() {
+      srm::read(path).map(|data| match self.region {
+        Some(region) => data[region.range()].to_vec(),
+        None => data,
+      })
+    } else {
+      std::fs::read(path)
+    };
+    let mut data = data.map_err(|e| GenericError::new(e, path.to_path_buf()))?;
+    if self.swap_bytes {
+      srm::word_swap(&mut data);
+    }
+    Ok(data)
+  }
+
+  fn show(&mut self, ui: &mut egui::Ui, id: &str) -> bool {
+    let mut changed = false;
+    egui::Grid::new(id).num_columns(2).show(ui, |ui| {
+      ui.label("File");
+      changed |= ui
+        .browse(&mut self.path, Browse::pick_file(&SAVE_FILTERS))
+        .changed();
+      ui.end_row();
+
+      if self.is_srm() {
+        ui.label("Region");
+        egui::ComboBox::from_id_source((id, "region"))
+          .selected_text(self.region.map_or("Whole File".into(), |r| r.to_string()))
+          .show_ui(ui, |ui| {
+            changed |= ui
+              .selectable_value(&mut self.region, None, "Whole File")
+              .changed();
+            for region in Region::ALL {
+              changed |= ui
+                .selectable_value(&mut self.region, Some(region), region.to_string())
+                .changed();
+            }
+          });
+        ui.end_row();
+      }
+
+      ui.label("");
+      changed |= ui
+        .checkbox(&mut self.swap_bytes, "Byte-swapped")
+        .on_hover_text("Swap the byte order of each 32 bit word, as in native EEP and FLA files")
+        .changed();
+      ui.end_row();
+    });
+    changed
+  }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Compare {
+  left: Side,
+  right: Side,
+  /// The differences, and the byte order of the second side which matches the first best when
+  /// either holds EEPROM or FlashRAM data
+  result: Option<Result<(Diff, Option<ByteOrder>), GenericError>>,
+}
+
+impl Compare {
+  fn run(&mut self) {
+    let (Some(left), Some(right)) = (&self.left.path, &self.right.path) else {
+      self.result = None;
+      return;
+    };
+    let battery = self.left.is_battery() || self.right.is_battery();
+    self.result = Some(self.left.load(left).and_then(|left| {
+      let right = self.right.load(right)?;
+      Ok(if battery {
+        let (diff, order) = diff_orders(&left, &right);
+        (diff, Some(order))
+      } else {
+        (diff(&left, &right), None)
+      })
+    }));
+  }
+
+  fn show_diff(ui: &mut egui::Ui, diff: &Diff, order: Option<ByteOrder>) {
+    match order {
+      Some(ByteOrder::Srm) => {
+        ui.label("Both sides match best in the same byte order.");
+      }
+      Some(ByteOrder::Swapped) => {
+        ui.label("The sides match best with the second one word-swapped, as compared below.");
+      }
+      None => {}
+    }
+    let (left_len, right_len) = diff.lengths;
+    if left_len != right_len {
+      ui.colored_label(
+        ui.visuals().warn_fg_color,
+        format!(
+          "Sizes differ: {left_len} vs {right_len} bytes, only the first {} bytes were compared",
+          left_len.min(right_len)
+        ),
+      );
+    }
+    if diff.ranges.is_empty() {
+      ui.label("The compared bytes are identical.");
+      return;
+    }
+    ui.label(format!(
+      "{} changed bytes in {} ranges",
+      diff.changed_bytes,
+      diff.ranges.len()
+    ));
+
+    ui.add_space(3.0);
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+    egui::ScrollArea::vertical()
+      .max_height(ui.ctx().screen_rect().height() * 0.4)
+      .show_rows(ui, row_height, diff.ranges.len(), |ui, rows| {
+        egui::Grid::new("compare_ranges")
+          .num_columns(3)
+          .striped(true)
+          .show(ui, |ui| {
+            for range in &diff.ranges[rows] {
+              ui.monospace(format!("{:#07x}", range.start));
+              ui.monospace(format!("{:#07x}", range.end - 1));
+              ui.label(format!("{} bytes", range.len()));
+              ui.end_row();
+            }
+          });
+      });
+  }
+
+  pub fn show(&mut self, ui: &mut egui::Ui) {
+    let mut changed = false;
+    ui.heading("First");
+    changed |= self.left.show(ui, "compare_left");
+    ui.add_space(6.0);
+    ui.heading("Second");
+    changed |= self.right.show(ui, "compare_right");
+
+    if changed {
+      self.run();
+    }
+
+    ui.separator();
+    match &self.result {
+      None => {
+        ui.label("Select the two saves to compare.");
+      }
+      Some(Err(error)) => {
+        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+      }
+      Some(Ok((diff, order))) => Self::show_diff(ui, diff, *order),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn diff_ranges() {
+    let diff = diff(&[0, 1, 2, 3, 4, 5], &[0, 9, 9, 3, 9, 5, 6]);
+    assert_eq!(diff.ranges, vec![1..3, 4..5]);
+    assert_eq!(diff.changed_bytes, 3);
+    assert_eq!(diff.lengths, (6, 7));
+  }
+
+  #[test]
+  fn diff_byte_orders() {
+    let left = [1, 2, 3, 4, 5, 6, 7, 8];
+    let (diff, order) = diff_orders(&left, &[4, 3, 2, 1, 8, 7, 6, 9]);
+    assert_eq!(order, ByteOrder::Swapped);
+    assert_eq!(diff.ranges, vec![4..5]);
+    let (diff, order) = diff_orders(&left, &left);
+    assert_eq!(order, ByteOrder::Srm);
+    assert!(diff.ranges.is_empty());
+  }
+}
@@ -0,0 +1,710 @@
+use super::srm::{CONTROLLER_PACKS_OFFSET, SRM_SIZE};
+
+pub(crate) const PACK_SIZE: usize = 0x8000;
+pub(crate) const MUPEN_PACK_SIZE: usize = 4 * PACK_SIZE;
+
+const PAGE_SIZE: usize = 0x100;
+const PAGE_COUNT: usize = PACK_SIZE / PAGE_SIZE;
+const FIRST_DATA_PAGE: usize = 5;
+
+const ID_BLOCKS: [usize; 4] = [0x20, 0x60, 0x80, 0xc0];
+const ID_BLOCK_SIZE: usize = 32;
+const INDEX_TABLE: usize = 0x100;
+const INDEX_BACKUP: usize = 0x200;
+const NOTE_TABLE: usize = 0x300;
+const NOTE_SIZE: usize = 32;
+const NOTE_COUNT: usize = 16;
+
+const LAST_PAGE: u16 = 1;
+const FREE_PAGE: u16 = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Issue {
+  IdBlockChecksum(usize),
+  NoValidIdBlock,
+  IndexChecksum,
+  IndexBackupMismatch,
+  InvalidIndexEntries(usize),
+  InvalidNoteStart { note: usize, page: u16 },
+  BrokenChain { note: usize, page: usize },
+  CrossLinked { note: usize, page: usize },
+  OrphanedPages(usize),
+  UnnamedNote(usize),
+}
+
+impl std::fmt::Display for Issue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Issue::IdBlockChecksum(block) => {
+        f.write_fmt(format_args!("ID block {} has a bad checksum", block + 1))
+      }
+      Issue::NoValidIdBlock => f.write_str("no ID block has a valid checksum"),
+      Issue::IndexChecksum => f.write_str("index table checksum mismatch"),
+      Issue::IndexBackupMismatch => f.write_str("index table differs from its backup"),
+      Issue::InvalidIndexEntries(count) => {
+        f.write_fmt(format_args!("{count} index entries have invalid values"))
+      }
+      Issue::InvalidNoteStart { note, page } => f.write_fmt(format_args!(
+        "note {} starts at invalid page {page}",
+        note + 1
+      )),
+      Issue::BrokenChain { note, page } => f.write_fmt(format_args!(
+        "note {} has a broken page chain at page {page}",
+        note + 1
+      )),
+      Issue::CrossLinked { note, page } => f.write_fmt(format_args!(
+        "note {} shares page {page} with another note",
+        note + 1
+      )),
+      Issue::OrphanedPages(count) => {
+        f.write_fmt(format_args!("{count} pages are used but belong to no note"))
+      }
+      Issue::UnnamedNote(note) => f.write_fmt(format_args!("note {} has no name", note + 1)),
+    }
+  }
+}
+
+fn checksum1(block: &[u8]) -> [u8; 2] {
+  block[0..28]
+    .chunks(2)
+    .fold(0u16, |sum, half_word| {
+      sum.wrapping_add(u16::from_be_bytes([half_word[0], half_word[1]]))
+    })
+    .to_be_bytes()
+}
+
+fn checksum2(sum1: &[u8]) -> [u8; 2] {
+  0xfff2u16
+    .wrapping_sub(u16::from_be_bytes([sum1[0], sum1[1]]))
+    .to_be_bytes()
+}
+
+fn is_id_block_valid(block: &[u8]) -> bool {
+  checksum1(block) == block[28..30] && checksum2(&block[28..30]) == block[30..32]
+}
+
+fn write_id_blocks(pack: &mut [u8]) {
+  const MUPEN64_SERIAL: [u8; 24] = [
+    0xff, 0xff, 0xff, 0xff, 0x05, 0x1a, 0x5f, 0x13, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+  ];
+
+  let block = &mut pack[ID_BLOCKS[0]..ID_BLOCKS[0] + ID_BLOCK_SIZE];
+  block[..24].copy_from_slice(&MUPEN64_SERIAL);
+  block[24..28].copy_from_slice(&[0xff, 0xff, 0x01, 0xff]);
+  let sum1 = checksum1(block);
+  block[28..30].copy_from_slice(&sum1);
+  let sum2 = checksum2(&sum1);
+  block[30..32].copy_from_slice(&sum2);
+
+  for offset in &ID_BLOCKS[1..] {
+    pack.copy_within(ID_BLOCKS[0]..ID_BLOCKS[0] + ID_BLOCK_SIZE, *offset);
+  }
+}
+
+fn index_entry(table: &[u8], page: usize) -> u16 {
+  u16::from_be_bytes([table[2 * page], table[2 * page + 1]])
+}
+
+fn set_index_entry(table: &mut [u8], page: usize, value: u16) {
+  table[2 * page..2 * page + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+fn index_checksum(table: &[u8]) -> u8 {
+  table[2 * FIRST_DATA_PAGE..]
+    .iter()
+    .fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+fn is_data_page(page: usize) -> bool {
+  (FIRST_DATA_PAGE..PAGE_COUNT).contains(&page)
+}
+
+fn is_note_used(note: &[u8]) -> bool {
+  note[..4].iter().any(|b| *b != 0)
+}
+
+fn note_start(note: &[u8]) -> u16 {
+  u16::from_be_bytes([note[6], note[7]])
+}
+
+fn notes(pack: &[u8]) -> std::slice::Chunks<'_, u8> {
+  pack[NOTE_TABLE..NOTE_TABLE + NOTE_COUNT * NOTE_SIZE].chunks(NOTE_SIZE)
+}
+
+/// Follows the page chain starting at `start`, returning the offending page if it is broken
+fn walk_chain(table: &[u8], start: usize) -> Result<Vec<usize>, usize> {
+  let mut pages = Vec::new();
+  let mut page = start;
+  loop {
+    if !is_data_page(page) || pages.contains(&page) {
+      return Err(page);
+    }
+    pages.push(page);
+    match index_entry(table, page) {
+      LAST_PAGE => return Ok(pages),
+      next => page = next as usize,
+    }
+  }
+}
+
+/// Checks a single controller pack for inconsistencies
+pub(crate) fn verify(pack: &[u8]) -> Vec<Issue> {
+  let mut issues = Vec::new();
+
+  let valid_ids = ID_BLOCKS.map(|o| is_id_block_valid(&pack[o..o + ID_BLOCK_SIZE]));
+  if valid_ids.iter().any(|v| *v) {
+    issues.extend(
+      (0..ID_BLOCKS.len())
+        .filter(|i| !valid_ids[*i])
+        .map(Issue::IdBlockChecksum),
+    );
+  } else {
+    issues.push(Issue::NoValidIdBlock);
+  }
+
+  let table = &pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE];
+  if table[1] != index_checksum(table) {
+    issues.push(Issue::IndexChecksum);
+  }
+  if table != &pack[INDEX_BACKUP..INDEX_BACKUP + PAGE_SIZE] {
+    issues.push(Issue::IndexBackupMismatch);
+  }
+  let invalid = (FIRST_DATA_PAGE..PAGE_COUNT)
+    .map(|page| index_entry(table, page))
+    .filter(|value| *value != LAST_PAGE && *value != FREE_PAGE && !is_data_page(*value as usize))
+    .count();
+  if invalid > 0 {
+    issues.push(Issue::InvalidIndexEntries(invalid));
+  }
+
+  let mut owned = [false; PAGE_COUNT];
+  for (note, entry) in notes(pack).enumerate() {
+    if !is_note_used(entry) {
+      continue;
+    }
+    let start = note_start(entry);
+    if !is_data_page(start as usize) {
+      issues.push(Issue::InvalidNoteStart { note, page: start });
+      continue;
+    }
+    match walk_chain(table, start as usize) {
+      Ok(pages) => {
+        if let Some(page) = pages.iter().find(|p| owned[**p]) {
+          issues.push(Issue::CrossLinked { note, page: *page });
+        }
+        pages.into_iter().for_each(|p| owned[p] = true);
+      }
+      Err(page) => issues.push(Issue::BrokenChain { note, page }),
+    }
+    if entry[16..].iter().all(|b| *b == 0) {
+      issues.push(Issue::UnnamedNote(note));
+    }
+  }
+
+  let orphaned = (FIRST_DATA_PAGE..PAGE_COUNT)
+    .filter(|p| !owned[*p] && index_entry(table, *p) != FREE_PAGE)
+    .count();
+  if orphaned > 0 {
+    issues.push(Issue::OrphanedPages(orphaned));
+  }
+
+  issues
+}
+
+/// Fixes a single controller pack in place, returning the issues found before the repair.
+///
+/// Notes with broken or shared page chains are dropped, and pages owned by no note are freed.
+pub(crate) fn repair(pack: &mut [u8]) -> Vec<Issue> {
+  let issues = verify(pack);
+  if issues.is_empty() {
+    return issues;
+  }
+
+  match ID_BLOCKS
+    .iter()
+    .find(|o| is_id_block_valid(&pack[**o..**o + ID_BLOCK_SIZE]))
+  {
+    Some(&good) => {
+      for offset in ID_BLOCKS {
+        if !is_id_block_valid(&pack[offset..offset + ID_BLOCK_SIZE]) {
+          pack.copy_within(good..good + ID_BLOCK_SIZE, offset);
+        }
+      }
+    }
+    None => write_id_blocks(pack),
+  }
+
+  let mut table = [0u8; PAGE_SIZE];
+  let backup = &pack[INDEX_BACKUP..INDEX_BACKUP + PAGE_SIZE];
+  if pack[INDEX_TABLE + 1] != index_checksum(&pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE])
+    && backup[1] == index_checksum(backup)
+  {
+    table.copy_from_slice(backup);
+  } else {
+    table.copy_from_slice(&pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE]);
+  }
+
+  let mut owned = [false; PAGE_COUNT];
+  for note in 0..NOTE_COUNT {
+    let entry = NOTE_TABLE + note * NOTE_SIZE..NOTE_TABLE + (note + 1) * NOTE_SIZE;
+    if !is_note_used(&pack[entry.clone()]) {
+      continue;
+    }
+    let start = note_start(&pack[entry.clone()]) as usize;
+    let pages = is_data_page(start)
+      .then(|| walk_chain(&table, start).ok())
+      .flatten()
+      .filter(|pages| pages.iter().all(|p| !owned[*p]));
+    match pages {
+      Some(pages) => pages.into_iter().for_each(|p| owned[p] = true),
+      None => pack[entry].fill(0),
+    }
+  }
+
+  for page in (FIRST_DATA_PAGE..PAGE_COUNT).filter(|p| !owned[*p]) {
+    set_index_entry(&mut table, page, FREE_PAGE);
+  }
+  table[1] = index_checksum(&table);
+
+  pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE].copy_from_slice(&table);
+  pack[INDEX_BACKUP..INDEX_BACKUP + PAGE_SIZE].copy_from_slice(&table);
+
+  issues
+}
+
+/// Gets the offsets of the controller packs inside a save file of the given size
+pub(crate) fn pack_offsets(len: usize) -> Option<Vec<usize>> {
+  match len {
+    PACK_SIZE => Some(vec![0]),
+    MUPEN_PACK_SIZE => Some((0..4).map(|i| i * PACK_SIZE).collect()),
+    SRM_SIZE => Some(
+      (0..4)
+        .map(|i| CONTROLLER_PACKS_OFFSET + i * PACK_SIZE)
+        .collect(),
+    ),
+    _ => None,
+  }
+}
+
+/// Initializes an empty controller pack
+pub(crate) fn format(pack: &mut [u8]) {
+  pack.fill(0);
+  pack[0] = 0x81;
+  for (i, b) in pack[..32].iter_mut().enumerate().skip(1) {
+    *b = i as u8;
+  }
+  write_id_blocks(pack);
+
+  let table = &mut pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE];
+  for page in FIRST_DATA_PAGE..PAGE_COUNT {
+    set_index_entry(table, page, FREE_PAGE);
+  }
+  table[1] = index_checksum(table);
+  pack.copy_within(INDEX_TABLE..INDEX_TABLE + PAGE_SIZE, INDEX_BACKUP);
+}
+
+/// Checks if no data page of the pack is allocated
+pub(crate) fn is_blank(pack: &[u8]) -> bool {
+  let table = &pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE];
+  (FIRST_DATA_PAGE..PAGE_COUNT).all(|p| index_entry(table, p) == FREE_PAGE)
+}
+
+/// Checks if the pack was never used, either formatted without notes or filled with one value
+fn is_unused(pack: &[u8]) -> bool {
+  is_blank(pack) || pack.iter().all(|b| *b == 0x00) || pack.iter().all(|b| *b == 0xff)
+}
+
+/// Gets the number of data pages not used by any note
+pub(crate) fn free_pages(pack: &[u8]) -> usize {
+  let table = &pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE];
+  (FIRST_DATA_PAGE..PAGE_COUNT)
+    .filter(|p| index_entry(table, *p) == FREE_PAGE)
+    .count()
+}
+
+pub(crate) const DATA_PAGES: usize = PAGE_COUNT - FIRST_DATA_PAGE;
+
+/// A game note, with its data pages
+#[derive(Clone, Debug)]
+pub(crate) struct Note {
+  entry: [u8; NOTE_SIZE],
+  data: Vec<u8>,
+}
+
+fn decode_char(c: u8) -> Option<char> {
+  const SYMBOLS: &str = "!\"#'*+,-./:=?@";
+  match c {
+    0x00 => None,
+    0x0f => Some(' '),
+    0x10..=0x19 => Some((b'0' + c - 0x10) as char),
+    0x1a..=0x33 => Some((b'A' + c - 0x1a) as char),
+    0x34..=0x41 => SYMBOLS.chars().nth((c - 0x34) as usize),
+    _ => Some('·'),
+  }
+}
+
+impl Note {
+  pub(crate) fn pages(&self) -> usize {
+    self.data.len() / PAGE_SIZE
+  }
+
+  pub(crate) fn name(&self) -> String {
+    let name = self.entry[16..].iter().filter_map(|c| decode_char(*c));
+    let ext = self.entry[12..16]
+      .iter()
+      .filter_map(|c| decode_char(*c))
+      .collect::<String>();
+    if ext.is_empty() {
+      name.collect()
+    } else {
+      name
+        .chain(std::iter::once('.'))
+        .chain(ext.chars())
+        .collect()
+    }
+  }
+
+  /// Checks if both notes belong to the same game and have the same name
+  pub(crate) fn collides_with(&self, other: &Note) -> bool {
+    self.entry[..6] == other.entry[..6] && self.entry[12..] == other.entry[12..]
+  }
+}
+
+/// Gets all the notes of a pack whose page chains are intact
+pub(crate) fn read_notes(pack: &[u8]) -> Vec<Note> {
+  let table = &pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE];
+  notes(pack)
+    .filter(|entry| is_note_used(entry))
+    .filter_map(|entry| {
+      let pages = walk_chain(table, note_start(entry) as usize).ok()?;
+      Some(Note {
+        entry: entry.try_into().unwrap(),
+        data: pages
+          .into_iter()
+          .flat_map(|p| &pack[p * PAGE_SIZE..(p + 1) * PAGE_SIZE])
+          .copied()
+          .collect(),
+      })
+    })
+    .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InsertError {
+  NoFreeNote,
+  NoFreePages,
+}
+
+impl std::fmt::Display for InsertError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      InsertError::NoFreeNote => f.write_str("all 16 notes are used"),
+      InsertError::NoFreePages => f.write_str("not enough free pages"),
+    }
+  }
+}
+
+/// Copies a note into the first free note entry and free pages of the pack
+pub(crate) fn insert_note(pack: &mut [u8], note: &Note) -> Result<(), InsertError> {
+  let slot = notes(pack)
+    .position(|entry| !is_note_used(entry))
+    .ok_or(InsertError::NoFreeNote)?;
+
+  let mut table = [0u8; PAGE_SIZE];
+  table.copy_from_slice(&pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE]);
+  let pages = (FIRST_DATA_PAGE..PAGE_COUNT)
+    .filter(|p| index_entry(&table, *p) == FREE_PAGE)
+    .take(note.pages())
+    .collect::<Vec<_>>();
+  if pages.len() < note.pages() || pages.is_empty() {
+    return Err(InsertError::NoFreePages);
+  }
+
+  for (i, (page, data)) in pages.iter().zip(note.data.chunks(PAGE_SIZE)).enumerate() {
+    pack[page * PAGE_SIZE..(page + 1) * PAGE_SIZE].copy_from_slice(data);
+    let next = pages.get(i + 1).map_or(LAST_PAGE, |p| *p as u16);
+    set_index_entry(&mut table, *page, next);
+  }
+  table[1] = index_checksum(&table);
+  pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE].copy_from_slice(&table);
+  pack[INDEX_BACKUP..INDEX_BACKUP + PAGE_SIZE].copy_from_slice(&table);
+
+  let mut entry = note.entry;
+  entry[6..8].copy_from_slice(&(pages[0] as u16).to_be_bytes());
+  let offset = NOTE_TABLE + slot * NOTE_SIZE;
+  pack[offset..offset + NOTE_SIZE].copy_from_slice(&entry);
+  Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) struct PackIssue {
+  pub path: std::path::PathBuf,
+  pub pack: Option<usize>,
+  pub issue: Issue,
+}
+
+impl std::fmt::Display for PackIssue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+    match self.pack {
+      Some(pack) => f.write_fmt(format_args!("{name} (Pack {}): {}", pack + 1, self.issue)),
+      None => f.write_fmt(format_args!("{name}: {}", self.issue)),
+    }
+  }
+}
+
+impl std::error::Error for PackIssue {}
+
+#[derive(Debug)]
+pub(crate) struct CheckedFile {
+  pub path: std::path::PathBuf,
+  pub issues: Vec<PackIssue>,
+  pub repaired: Option<std::path::PathBuf>,
+}
+
+impl std::fmt::Display for CheckedFile {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+    match (self.issues.len(), &self.repaired) {
+      (0, _) => f.write_fmt(format_args!("{name}: OK")),
+      (count, None) => f.write_fmt(format_args!("{name}: {count} problem(s) found")),
+      (count, Some(repaired)) => f.write_fmt(format_args!(
+        "{name}: {count} problem(s) fixed in {}",
+        repaired.file_name().unwrap_or_default().to_string_lossy()
+      )),
+    }
+  }
+}
+
+fn repaired_path(path: &std::path::Path) -> std::path::PathBuf {
+  let mut name = path.file_stem().unwrap_or_default().to_owned();
+  name.push("_repaired");
+  if let Some(ext) = path.extension() {
+    name.push(".");
+    name.push(ext);
+  }
+  path.with_file_name(name)
+}
+
+/// Verifies every controller pack in the file, optionally writing a repaired copy next to it
+pub(crate) fn check_file(
+  path: std::path::PathBuf,
+  repair_copy: bool,
+) -> std::io::Result<CheckedFile> {
+  let mut data = std::fs::read(&path)?;
+  let Some(offsets) = pack_offsets(data.len()) else {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::InvalidData,
+      "not a controller pack or uncompressed SRM",
+    ));
+  };
+
+  let multi_pack = offsets.len() > 1;
+  let mut issues = Vec::new();
+  for (i, offset) in offsets.into_iter().enumerate() {
+    let pack = &mut data[offset..offset + PACK_SIZE];
+    if is_unused(pack) {
+      continue;
+    }
+    let found = if repair_copy {
+      repair(pack)
+    } else {
+      verify(pack)
+    };
+    issues.extend(found.into_iter().map(|issue| PackIssue {
+      path: path.clone(),
+      pack: multi_pack.then_some(i),
+      issue,
+    }));
+  }
+
+  let repaired = if repair_copy && !issues.is_empty() {
+    // an earlier repaired copy is kept
+    let mut repaired = repaired_path(&path);
+    if repaired.exists() {
+      repaired = super::output::keep_both_path(&repaired);
+    }
+    std::io::Write::write_all(
+      &mut std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&repaired)?,
+      &data,
+    )?;
+    Some(repaired)
+  } else {
+    None
+  };
+
+  Ok(CheckedFile {
+    path,
+    issues,
+    repaired,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::test_dir::TestDir;
+
+  fn new_pack() -> Vec<u8> {
+    let mut pack = vec![0u8; PACK_SIZE];
+    format(&mut pack);
+    pack
+  }
+
+  fn add_note(pack: &mut [u8], note: usize, pages: &[usize]) {
+    let entry = &mut pack[NOTE_TABLE + note * NOTE_SIZE..NOTE_TABLE + (note + 1) * NOTE_SIZE];
+    entry[..4].copy_from_slice(b"NZSE");
+    entry[6..8].copy_from_slice(&(pages[0] as u16).to_be_bytes());
+    entry[16] = 0x1a;
+    let table = &mut pack[INDEX_TABLE..INDEX_TABLE + PAGE_SIZE];
+    for (i, page) in pages.iter().enumerate() {
+      let next = pages.get(i + 1).map_or(LAST_PAGE, |p| *p as u16);
+      set_index_entry(table, *page, next);
+    }
+    table[1] = index_checksum(table);
+    pack.copy_within(INDEX_TABLE..INDEX_TABLE + PAGE_SIZE, INDEX_BACKUP);
+  }
+
+  #[test]
+  fn verify_clean_pack() {
+    let mut pack = new_pack();
+    assert!(verify(&pack).is_empty());
+
+    add_note(&mut pack, 0, &[5, 6, 9]);
+    add_note(&mut pack, 3, &[7]);
+    assert!(verify(&pack).is_empty());
+  }
+
+  #[test]
+  fn verify_detects_problems() {
+    let mut pack = new_pack();
+    add_note(&mut pack, 0, &[5, 6]);
+    pack[ID_BLOCKS[2]] ^= 0xff;
+    set_index_entry(&mut pack[INDEX_TABLE..], 10, LAST_PAGE);
+
+    let issues = verify(&pack);
+    assert!(issues.contains(&Issue::IdBlockChecksum(2)));
+    assert!(issues.contains(&Issue::IndexChecksum));
+    assert!(issues.contains(&Issue::IndexBackupMismatch));
+    assert!(issues.contains(&Issue::OrphanedPages(1)));
+  }
+
+  #[test]
+  fn verify_detects_broken_and_shared_chains() {
+    let mut pack = new_pack();
+    add_note(&mut pack, 0, &[5, 6]);
+    add_note(&mut pack, 1, &[8, 6]);
+    add_note(&mut pack, 2, &[10]);
+    set_index_entry(&mut pack[INDEX_TABLE..], 10, 2);
+
+    let issues = verify(&pack);
+    assert!(issues.contains(&Issue::CrossLinked { note: 1, page: 6 }));
+    assert!(issues.contains(&Issue::BrokenChain { note: 2, page: 2 }));
+  }
+
+  #[test]
+  fn repair_fixes_pack() {
+    let mut pack = new_pack();
+    add_note(&mut pack, 0, &[5, 6]);
+    add_note(&mut pack, 1, &[7, 6]);
+    set_index_entry(&mut pack[INDEX_TABLE..], 20, LAST_PAGE);
+    for offset in ID_BLOCKS {
+      pack[offset] ^= 0xff;
+    }
+
+    assert!(!repair(&mut pack).is_empty());
+    assert!(verify(&pack).is_empty());
+    // the first note is kept, the one sharing its page is dropped
+    assert!(is_note_used(&pack[NOTE_TABLE..NOTE_TABLE + NOTE_SIZE]));
+    assert!(!is_note_used(
+      &pack[NOTE_TABLE + NOTE_SIZE..NOTE_TABLE + 2 * NOTE_SIZE]
+    ));
+    assert_eq!(index_entry(&pack[INDEX_TABLE..], 7), FREE_PAGE);
+  }
+
+  #[test]
+  fn check_unused_packs() {
+    let dir = TestDir::new("check");
+    let path = dir.join("a.srm");
+    std::fs::write(&path, vec![0u8; SRM_SIZE]).unwrap();
+    let checked = check_file(path, true).unwrap();
+    assert!(checked.issues.is_empty());
+    assert_eq!(checked.repaired, None);
+
+    let mut pack = vec![0u8; PACK_SIZE];
+    assert_eq!(
+      verify(&pack)[..2],
+      [
+        Issue::NoValidIdBlock,
+        Issue::InvalidIndexEntries(DATA_PAGES)
+      ]
+    );
+    pack.fill(0xff);
+    assert!(is_unused(&pack));
+  }
+
+  #[test]
+  fn keep_earlier_repairs() {
+    let dir = TestDir::new("repair");
+    let path = dir.join("a.mpk");
+    let mut pack = new_pack();
+    add_note(&mut pack, 0, &[5, 6]);
+    for offset in ID_BLOCKS {
+      pack[offset] ^= 0xff;
+    }
+    std::fs::write(&path, &pack).unwrap();
+
+    let first = check_file(path.clone(), true).unwrap().repaired;
+    let second = check_file(path.clone(), true).unwrap().repaired;
+    assert_eq!(first, Some(dir.join("a_repaired.mpk")));
+    assert_eq!(second, Some(dir.join("a_repaired (1).mpk")));
+  }
+
+  #[test]
+  fn insert_notes_into_pack() {
+    let mut source = new_pack();
+    add_note(&mut source, 2, &[30, 12, 7]);
+    source[12 * PAGE_SIZE] = 0x42;
+    let notes = read_notes(&source);
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].pages(), 3);
+    assert_eq!(notes[0].name(), "A");
+
+    let mut target = new_pack();
+    assert_eq!(insert_note(&mut target, &notes[0]), Ok(()));
+    assert!(verify(&target).is_empty());
+    assert_eq!(free_pages(&target), DATA_PAGES - 3);
+
+    let merged = read_notes(&target);
+    assert!(merged[0].collides_with(&notes[0]));
+    assert_eq!(merged[0].data, notes[0].data);
+  }
+
+  #[test]
+  fn insert_note_without_space() {
+    let mut target = new_pack();
+    add_note(
+      &mut target,
+      0,
+      &(FIRST_DATA_PAGE..PAGE_COUNT - 1).collect::<Vec<_>>(),
+    );
+
+    let mut source = new_pack();
+    add_note(&mut source, 0, &[5, 6]);
+    let note = &read_notes(&source)[0];
+    assert_eq!(
+      insert_note(&mut target, note),
+      Err(InsertError::NoFreePages)
+    );
+
+    let mut full = new_pack();
+    for i in 0..NOTE_COUNT {
+      add_note(&mut full, i, &[FIRST_DATA_PAGE + i]);
+    }
+    assert_eq!(insert_note(&mut full, note), Err(InsertError::NoFreeNote));
+  }
+}
@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+const APP_DIR: &str = "ramp64-convert-gui";
+
+/// Gets the directory where this application keeps its own files, creating it if needed
+pub(crate) fn data_dir() -> std::io::Result<PathBuf> {
+  let base = if cfg!(windows) {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+  } else if cfg!(target_os = "macos") {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+  } else {
+    std::env::var_os("XDG_DATA_HOME")
+      .filter(|dir| !dir.is_empty())
+      .map(PathBuf::from)
+      .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+  };
+  let Some(base) = base else {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::NotFound,
+      "no home directory",
+    ));
+  };
+  let dir = base.join(APP_DIR);
+  std::fs::create_dir_all(&dir)?;
+  Ok(dir)
+}
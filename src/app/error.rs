@@ -0,0 +1,212 @@
+use super::{
+  controller_pack::PackIssue,
+  output::{TempFileError, TemplateError},
+  work::GenericError,
+};
+
+use std::path::{Path, PathBuf};
+
+/// What went wrong, to group the errors and suggest how to fix them
+#[derive(Debug)]
+pub(crate) enum ErrorKind {
+  /// A folder could not be scanned for saves
+  Scan(std::io::Error),
+  /// A group is missing files, or has files which do not go together
+  Validation(String),
+  /// Reading or writing a file failed
+  Io(std::io::Error),
+  /// The file system denied access to a file
+  Permission(std::io::Error),
+  /// A file does not hold what its type says, or a name template is invalid
+  Format(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// An error, with the file and group it happened to
+#[derive(Debug)]
+pub(crate) struct AppError {
+  pub kind: ErrorKind,
+  pub path: Option<PathBuf>,
+  pub group: Option<String>,
+}
+
+impl AppError {
+  fn new(kind: ErrorKind) -> Self {
+    Self {
+      kind,
+      path: None,
+      group: None,
+    }
+  }
+
+  /// Sorts the I/O error by whether access was denied
+  pub fn io(error: std::io::Error, path: impl Into<PathBuf>) -> Self {
+    let kind = match error.kind() {
+      std::io::ErrorKind::PermissionDenied => ErrorKind::Permission(error),
+      _ => ErrorKind::Io(error),
+    };
+    Self::new(kind).at(path)
+  }
+
+  pub(super) fn scan(error: GenericError) -> Self {
+    Self::new(ErrorKind::Scan(error.error)).at(error.path)
+  }
+
+  pub fn validation(message: impl std::fmt::Display) -> Self {
+    Self::new(ErrorKind::Validation(message.to_string()))
+  }
+
+  pub fn format(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+    Self::new(ErrorKind::Format(error.into()))
+  }
+
+  /// Sets the file the error happened to, unless the path is empty
+  pub fn at(self, path: impl Into<PathBuf>) -> Self {
+    let path = path.into();
+    Self {
+      path: (path != Path::new("")).then_some(path),
+      ..self
+    }
+  }
+
+  pub fn in_group(self, group: impl Into<String>) -> Self {
+    Self {
+      group: Some(group.into()),
+      ..self
+    }
+  }
+
+  pub fn kind_name(&self) -> &str {
+    match self.kind {
+      ErrorKind::Scan(_) => "Scan",
+      ErrorKind::Validation(_) => "Validation",
+      ErrorKind::Io(_) => "I/O",
+      ErrorKind::Permission(_) => "Permission",
+      ErrorKind::Format(_) => "Format",
+    }
+  }
+
+  /// Suggests what the user can do about the error
+  pub fn suggestion(&self) -> &str {
+    match &self.kind {
+      ErrorKind::Scan(_) => "Check that the folder still exists and can be read.",
+      ErrorKind::Validation(_) => "Add the missing files to the group, or remove the group.",
+      ErrorKind::Io(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {
+        "Enable overwriting in the options, or choose another output folder."
+      }
+      ErrorKind::Io(error) if error.kind() == std::io::ErrorKind::NotFound => {
+        "Check that the file was not moved or deleted."
+      }
+      ErrorKind::Io(_) => "Check that the drive is connected and has free space, then retry.",
+      ErrorKind::Permission(_) => {
+        "Check the permissions of the file and its folder, or choose another output folder."
+      }
+      ErrorKind::Format(_) => "Check that the file is a save of the type its extension says.",
+    }
+  }
+}
+
+impl std::fmt::Display for AppError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if let Some(group) = &self.group {
+      f.write_fmt(format_args!("{group}: "))?;
+    }
+    if let (Some(path), false) = (&self.path, matches!(self.kind, ErrorKind::Validation(_))) {
+      f.write_fmt(format_args!("{}: ", path.display()))?;
+    }
+    match &self.kind {
+      ErrorKind::Scan(error) | ErrorKind::Io(error) | ErrorKind::Permission(error) => {
+        f.write_fmt(format_args!("{error}"))
+      }
+      ErrorKind::Validation(message) => f.write_str(message),
+      ErrorKind::Format(error) => f.write_fmt(format_args!("{error}")),
+    }
+  }
+}
+
+impl std::error::Error for AppError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match &self.kind {
+      ErrorKind::Scan(error) | ErrorKind::Io(error) | ErrorKind::Permission(error) => Some(error),
+      ErrorKind::Validation(_) => None,
+      ErrorKind::Format(error) => Some(error.as_ref()),
+    }
+  }
+}
+
+impl From<GenericError> for AppError {
+  fn from(error: GenericError) -> Self {
+    Self::io(error.error, error.path)
+  }
+}
+
+impl From<TempFileError> for AppError {
+  fn from(error: TempFileError) -> Self {
+    let io = std::io::Error::new(
+      error.error.kind(),
+      format!("could not remove temporary file: {}", error.error),
+    );
+    Self::io(io, error.path)
+  }
+}
+
+impl From<TemplateError> for AppError {
+  fn from(error: TemplateError) -> Self {
+    Self::format(error)
+  }
+}
+
+impl From<PackIssue> for AppError {
+  fn from(issue: PackIssue) -> Self {
+    let message = match issue.pack {
+      Some(pack) => format!("Pack {}: {}", pack + 1, issue.issue),
+      None => issue.issue.to_string(),
+    };
+    Self::format(message).at(issue.path)
+  }
+}
+
+impl From<ramp64_srm_convert_lib::Error> for AppError {
+  fn from(error: ramp64_srm_convert_lib::Error) -> Self {
+    // the library only exposes its message with the path in front, which is shown apart
+    let message = error.to_string();
+    let prefix = format!("\"{}\": ", error.path().display());
+    let message = message.strip_prefix(&prefix).unwrap_or(&message);
+    Self::io(std::io::Error::new(error.kind(), message), error.path())
+  }
+}
+
+impl From<ramp64_srm_convert_lib::io::Error> for AppError {
+  fn from(error: ramp64_srm_convert_lib::io::Error) -> Self {
+    match error {
+      ramp64_srm_convert_lib::io::Error::Other(error) => Self::io(error, PathBuf::new()),
+      error => Self::format(error),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sort_and_display() {
+    let denied = AppError::io(std::io::ErrorKind::PermissionDenied.into(), "out/Zelda.eep");
+    assert_eq!(denied.kind_name(), "Permission");
+    let error = AppError::from(GenericError::new(
+      std::io::ErrorKind::AlreadyExists.into(),
+      "out/Zelda.eep".into(),
+    ))
+    .in_group("Zelda");
+    assert_eq!(error.kind_name(), "I/O");
+    assert_eq!(error.path.as_deref(), Some(Path::new("out/Zelda.eep")));
+    assert_eq!(
+      error.to_string(),
+      "Zelda: out/Zelda.eep: entity already exists"
+    );
+    assert!(std::error::Error::source(&error).is_some());
+
+    let invalid = AppError::validation("no battery").at("").in_group("Mario");
+    assert_eq!(invalid.path, None);
+    assert_eq!(invalid.to_string(), "Mario: no battery");
+  }
+}
@@ -1,11 +1,14 @@
-use std::collections::HashMap;
+use super::error::AppError;
+
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 pub(crate) struct ErrorList<C>
 where
   C: Category,
 {
-  errors: HashMap<C, Vec<Box<dyn std::error::Error>>>,
+  /// Kept in category order, so the errors and their ids are stable between frames
+  errors: BTreeMap<C, Vec<AppError>>,
 }
 
 impl<C> Default for ErrorList<C>
@@ -21,48 +24,188 @@ where
 
 pub(crate) trait Category
 where
-  Self: PartialEq + Eq + std::hash::Hash,
+  Self: PartialEq + Eq + PartialOrd + Ord,
 {
   fn name(&self) -> &str;
   fn description(&self) -> &str;
 }
 
+/// What the user asked to do about the group of an error
+#[derive(Debug, PartialEq)]
+pub(crate) enum ErrorAction {
+  Retry(String),
+  RetryAll,
+  ShowGroup(String),
+  RemoveGroup(String),
+}
+
+/// Iterates the errors which caused the error, nearest first
+fn sources(error: &AppError) -> impl Iterator<Item = &dyn std::error::Error> {
+  std::iter::successors(std::error::Error::source(error), |e| e.source())
+}
+
+/// Describes the error with all its details, to paste in a support request
+fn describe(error: &AppError) -> String {
+  let mut text = format!("{error}\nKind: {}", error.kind_name());
+  if let Some(path) = &error.path {
+    text.push_str(&format!("\nPath: {}", path.display()));
+  }
+  for source in sources(error) {
+    text.push_str(&format!("\nCaused by: {source}"));
+  }
+  text
+}
+
+fn show_details(ui: &mut egui::Ui, id: egui::Id, error: &AppError) {
+  egui::Grid::new(id.with("details"))
+    .num_columns(2)
+    .show(ui, |ui| {
+      ui.weak("Kind");
+      ui.label(error.kind_name());
+      ui.end_row();
+      if let Some(path) = &error.path {
+        ui.weak("Path");
+        ui.horizontal(|ui| {
+          ui.label(path.display().to_string());
+          if ui
+            .small_button("Reveal")
+            .on_hover_text("Open the folder of the file")
+            .clicked()
+          {
+            let folder = match path.is_dir() {
+              true => path.as_path(),
+              false => path.parent().unwrap_or(path),
+            };
+            open::that_in_background(folder);
+          }
+        });
+        ui.end_row();
+      }
+      for source in sources(error) {
+        ui.weak("Caused by");
+        ui.label(source.to_string());
+        ui.end_row();
+      }
+      ui.weak("Suggestion");
+      ui.label(error.suggestion());
+      ui.end_row();
+    });
+  if ui.small_button("Copy").clicked() {
+    ui.output_mut(|o| o.copied_text = describe(error));
+  }
+}
+
+/// Shows the error with its details folded, and buttons to act on its group when it still exists
+fn show_error(
+  ui: &mut egui::Ui,
+  id: egui::Id,
+  error: &AppError,
+  has_group: &impl Fn(&str) -> bool,
+) -> Option<ErrorAction> {
+  let mut action = None;
+  egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+    .show_header(ui, |ui| {
+      ui.label(error.to_string());
+      let Some(group) = error.group.as_deref().filter(|g| has_group(g)) else {
+        return;
+      };
+      if ui.small_button("Retry").clicked() {
+        action = Some(ErrorAction::Retry(group.to_string()));
+      }
+      if ui.small_button("Show Group").clicked() {
+        action = Some(ErrorAction::ShowGroup(group.to_string()));
+      }
+      if ui.small_button("Remove Group").clicked() {
+        action = Some(ErrorAction::RemoveGroup(group.to_string()));
+      }
+    })
+    .body(|ui| show_details(ui, id, error));
+  action
+}
+
 impl<C> ErrorList<C>
 where
   C: Category,
 {
-  pub fn add(&mut self, category: C, error: impl Into<Box<dyn std::error::Error>>) {
-    self.errors.entry(category).or_default().push(error.into())
+  pub fn add(&mut self, category: C, error: impl Into<AppError>) {
+    let error = error.into();
+    log::error!("{}: {error}", category.name());
+    self.errors.entry(category).or_default().push(error)
   }
 
   pub fn has_errors(&self) -> bool {
     !self.errors.is_empty()
   }
 
+  pub fn len(&self) -> usize {
+    self.errors.values().map(Vec::len).sum()
+  }
+
   pub fn clear(&mut self) {
     self.errors.clear()
   }
 
-  fn show_all(&mut self, ui: &mut egui::Ui) {
+  /// Keeps only the errors for which `keep` is true
+  pub fn retain(&mut self, mut keep: impl FnMut(&AppError) -> bool) {
+    for errors in self.errors.values_mut() {
+      errors.retain(&mut keep);
+    }
+    self.errors.retain(|_, errors| !errors.is_empty());
+  }
+
+  /// Gets the groups of the errors which are accepted by `has_group`
+  pub fn groups(&self, has_group: impl Fn(&str) -> bool) -> std::collections::BTreeSet<String> {
+    self
+      .iter()
+      .filter_map(|(_, error)| error.group.clone())
+      .filter(|group| has_group(group))
+      .collect()
+  }
+
+  /// Iterates every error, along with its category
+  pub fn iter(&self) -> impl Iterator<Item = (&C, &AppError)> {
+    self
+      .errors
+      .iter()
+      .flat_map(|(category, errors)| errors.iter().map(move |error| (category, error)))
+  }
+
+  fn show_all(
+    &mut self,
+    ui: &mut egui::Ui,
+    has_group: &impl Fn(&str) -> bool,
+  ) -> Option<ErrorAction> {
+    let mut action = None;
     for (category, errors) in &self.errors {
-      egui::CollapsingHeader::new(egui::RichText::from(category.name()).heading())
+      let name = format!("{} ({})", category.name(), errors.len());
+      egui::CollapsingHeader::new(egui::RichText::from(name).heading())
+        .id_source(category.name())
         .default_open(true)
         .show(ui, |ui| {
-          for error in errors {
-            ui.label(error.to_string());
+          for (i, error) in errors.iter().enumerate() {
+            let id = ui.make_persistent_id((category.name(), i));
+            action = show_error(ui, id, error, has_group).or(action.take());
           }
         })
         .header_response
         .on_hover_text(category.description());
     }
+    action
   }
 
-  fn show_one(&mut self, ui: &mut egui::Ui) {
-    for (_, errors) in &self.errors {
-      for error in errors {
-        ui.label(error.to_string());
+  fn show_one(
+    &mut self,
+    ui: &mut egui::Ui,
+    has_group: &impl Fn(&str) -> bool,
+  ) -> Option<ErrorAction> {
+    let mut action = None;
+    for (category, errors) in &self.errors {
+      for (i, error) in errors.iter().enumerate() {
+        let id = ui.make_persistent_id((category.name(), i));
+        action = show_error(ui, id, error, has_group).or(action.take());
       }
     }
+    action
   }
 }
 
@@ -70,11 +213,63 @@ impl<C> ErrorList<C>
 where
   C: Category,
 {
-  pub fn show(&mut self, ui: &mut egui::Ui) {
-    if self.errors.len() != 1 {
-      self.show_all(ui);
+  /// Shows every error, returning what the user asked to do about a group.
+  ///
+  /// The group buttons are only shown for the groups accepted by `has_group`.
+  pub fn show(
+    &mut self,
+    ui: &mut egui::Ui,
+    has_group: impl Fn(&str) -> bool,
+  ) -> Option<ErrorAction> {
+    let mut action = if self.errors.len() != 1 {
+      self.show_all(ui, &has_group)
     } else {
-      self.show_one(ui);
+      self.show_one(ui, &has_group)
+    };
+    if !self.groups(&has_group).is_empty() {
+      ui.add_space(6.0);
+      if ui.button("Retry All").clicked() {
+        action = Some(ErrorAction::RetryAll);
+      }
     }
+    action
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+  enum Test {
+    A,
+    B,
+  }
+
+  impl Category for Test {
+    fn name(&self) -> &str {
+      "Test"
+    }
+
+    fn description(&self) -> &str {
+      ""
+    }
+  }
+
+  #[test]
+  fn retain_groups() {
+    let mut errors = ErrorList::default();
+    errors.add(Test::A, AppError::validation("a").in_group("Zelda"));
+    errors.add(Test::A, AppError::validation("b").in_group("Mario"));
+    errors.add(Test::B, AppError::validation("c").in_group("Gone"));
+    assert_eq!(
+      errors.groups(|g| g != "Gone"),
+      ["Mario".to_string(), "Zelda".to_string()].into()
+    );
+
+    errors.retain(|e| e.group.as_deref() != Some("Gone"));
+    assert_eq!(errors.errors.len(), 1);
+    errors.retain(|_| false);
+    assert!(!errors.has_errors());
   }
 }
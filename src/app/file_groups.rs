@@ -2,7 +2,18 @@ mod group_item;
 
 use self::group_item::Way;
 
-use super::{error_list::ErrorList, options::Options, ErrorCategory};
+use super::{
+  byte_order::{Detection, Probe},
+  collisions::{Collision, Kind as CollisionKind},
+  error::AppError,
+  error_list::ErrorList,
+  history::{GroupRun, Outcome},
+  options::Options,
+  output::{Decision, PlaceError, Placement, Staged},
+  overwrite::Question,
+  preflight::{self, Problem},
+  ErrorCategory,
+};
 
 use crate::widgets::{
   browser::{Browse, UiBrowser},
@@ -14,12 +25,67 @@ pub(crate) use group_item::GroupItem;
 
 use paste::paste;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug, Default)]
 pub(crate) struct FileGroups {
   groups: BTreeMap<String, GroupItem>,
   selection: Option<SelectionRange>,
+  last_action: Option<ItemAction>,
+  conversion: Option<Conversion>,
+}
+
+/// A conversion waiting for the user to decide about existing files
+#[derive(Debug)]
+struct Conversion {
+  staged: Vec<(String, Staged)>,
+  apply_to_all: Option<Decision>,
+  /// Groups which already failed to convert
+  failed: Vec<GroupRun>,
+}
+
+impl Conversion {
+  fn undecided(&mut self) -> impl Iterator<Item = &mut Placement> {
+    self
+      .staged
+      .iter_mut()
+      .flat_map(|(_, staged)| staged.placements.iter_mut())
+      .filter(|p| p.decision.is_none())
+  }
+}
+
+pub(crate) enum ConvertStatus {
+  /// Nothing was converted, as the preflight checks found problems
+  Problems(Vec<Problem>),
+  /// Nothing was converted, as some outputs collide
+  Collisions(Vec<Collision>),
+  /// The conversion waits for the user to decide about an existing file
+  Ask(Question),
+  /// The conversion finished
+  Done(Converted),
+}
+
+/// Groups which finished converting
+#[derive(Debug, Default)]
+pub(crate) struct Converted {
+  pub converted: Vec<String>,
+  /// Groups whose outputs were identical to the existing files
+  pub unchanged: Vec<String>,
+  /// What happened to every group, for the history
+  pub runs: Vec<GroupRun>,
+}
+
+impl Converted {
+  pub fn is_empty(&self) -> bool {
+    self.converted.is_empty() && self.unchanged.is_empty()
+  }
+}
+
+/// Requests made from the item options panel
+#[derive(Debug)]
+pub(crate) enum ItemAction {
+  ShowLayout(std::path::PathBuf),
+  ShowHex(std::path::PathBuf),
 }
 
 #[derive(Debug, Default)]
@@ -30,6 +96,15 @@ impl std::fmt::Display for DisplayPath {
   }
 }
 
+/// Gets the path used to compare targets, ignoring case where the file system does
+fn comparable_path(path: &std::path::Path) -> std::path::PathBuf {
+  if cfg!(any(windows, target_os = "macos")) {
+    path.to_string_lossy().to_lowercase().into()
+  } else {
+    path.to_path_buf()
+  }
+}
+
 fn get_group(path: &std::path::Path) -> String {
   use std::ffi::OsStr;
   path
@@ -40,18 +115,61 @@ fn get_group(path: &std::path::Path) -> String {
 }
 
 impl FileGroups {
-  pub fn add_file(&mut self, selected_file: std::path::PathBuf) {
-    let group = get_group(&selected_file);
+  /// Gets the group a file belongs to, the one holding the files with the same name in its folder.
+  ///
+  /// Files named as a group from another folder get their own group, named after their folder.
+  fn group_of(&self, path: &std::path::Path) -> String {
+    let name = get_group(path);
+    let Some(folder) = path.parent() else {
+      return name;
+    };
+    let in_folder = format!("{name} ({})", folder.display());
+    let candidates = [
+      Some(name.clone()),
+      folder
+        .file_name()
+        .map(|dir| format!("{name} ({})", dir.to_string_lossy())),
+      Some(in_folder.clone()),
+    ];
+    candidates
+      .into_iter()
+      .flatten()
+      .find(|key| {
+        self
+          .groups
+          .get(key)
+          .is_none_or(|group| group.folder() == Some(folder))
+      })
+      .unwrap_or(in_folder)
+  }
+
+  /// Adds the file to its group, returning the name of the group
+  pub fn add_file(&mut self, selected_file: std::path::PathBuf) -> String {
+    let group = self.group_of(&selected_file);
     self
       .groups
-      .entry(group)
+      .entry(group.clone())
       .and_modify(|g| g.set(selected_file.clone()))
       .or_insert_with(|| GroupItem::from(selected_file));
+    group
+  }
+
+  /// Adds the changed files, returning every other group so their conversion can be skipped
+  pub fn add_changed_files(&mut self, files: Vec<std::path::PathBuf>) -> BTreeSet<String> {
+    let changed = files
+      .into_iter()
+      .map(|file| self.add_file(file))
+      .collect::<BTreeSet<_>>();
+    self.others(&changed)
   }
 
-  pub fn add_files(&mut self, files: Vec<std::path::PathBuf>) {
+  /// Adds the files found scanning `root`, keeping it to mirror their folders in the output
+  pub fn add_scanned_files(&mut self, root: std::path::PathBuf, files: Vec<std::path::PathBuf>) {
     for file in files {
-      self.add_file(file)
+      let group = self.add_file(file);
+      if let Some(item) = self.groups.get_mut(&group) {
+        item.scan_root = Some(root.clone());
+      }
     }
   }
 
@@ -72,6 +190,46 @@ impl FileGroups {
     self.selection = None
   }
 
+  pub(crate) fn len(&self) -> usize {
+    self.groups.len()
+  }
+
+  pub(crate) fn valid_count(&self) -> usize {
+    self.groups.values().filter(|g| g.is_valid()).count()
+  }
+
+  pub(crate) fn selected_count(&self) -> usize {
+    self.selection.as_ref().map_or(0, SelectionRange::len)
+  }
+
+  pub(crate) fn contains(&self, group: &str) -> bool {
+    self.groups.contains_key(group)
+  }
+
+  /// Gets every group not in `groups`, so their conversion can be skipped
+  pub(crate) fn others(&self, groups: &BTreeSet<String>) -> BTreeSet<String> {
+    self
+      .groups
+      .keys()
+      .filter(|key| !groups.contains(*key))
+      .cloned()
+      .collect()
+  }
+
+  /// Selects only the group, showing its options
+  pub(crate) fn select(&mut self, group: &str) {
+    self.selection = self
+      .groups
+      .keys()
+      .position(|key| key == group)
+      .map(|i| SelectionRange::new(i..i + 1));
+  }
+
+  pub(crate) fn remove(&mut self, group: &str) {
+    self.groups.remove(group);
+    self.selection = None;
+  }
+
   pub(crate) fn remove_selected(&mut self) {
     if let Some(selection) = self.selection.take() {
       let mut ranges = selection.into_ranges();
@@ -87,54 +245,366 @@ impl FileGroups {
     }
   }
 
+  /// The groups which are converted, all but the skipped ones
+  fn converted_groups<'s>(
+    &'s self,
+    skipped: Option<&'s BTreeSet<String>>,
+  ) -> impl Iterator<Item = (&'s String, &'s GroupItem)> {
+    self
+      .groups
+      .iter()
+      .filter(move |(key, _)| !skipped.is_some_and(|s| s.contains(*key)))
+  }
+
+  /// Finds the output files written by more than one group, and the existing ones which would not
+  /// be overwritten
+  fn collisions(&self, options: &Options, skipped: Option<&BTreeSet<String>>) -> Vec<Collision> {
+    // the target, the groups writing it, and the groups which would not overwrite it
+    let mut targets: BTreeMap<std::path::PathBuf, (std::path::PathBuf, Vec<String>, Vec<String>)> =
+      BTreeMap::new();
+    for (key, group) in self.converted_groups(skipped) {
+      let group_options = group.options(options);
+      // existing files are left to the overwrite question when asking
+      let keeps_existing = !group_options.user_params.overwrite && !group_options.ask_overwrite;
+      // groups without targets fail later with their own error
+      for target in group.targets(options).unwrap_or_default() {
+        let exists = keeps_existing && target.exists();
+        let (_, groups, kept) = targets
+          .entry(comparable_path(&target))
+          .or_insert_with(|| (target, Vec::new(), Vec::new()));
+        groups.push(key.clone());
+        if exists {
+          kept.push(key.clone());
+        }
+      }
+    }
+    let mut collisions = Vec::new();
+    for (target, groups, kept) in targets.into_values() {
+      if groups.len() > 1 {
+        collisions.push(Collision {
+          target: target.clone(),
+          groups,
+          kind: CollisionKind::Shared,
+        });
+      }
+      if !kept.is_empty() {
+        collisions.push(Collision {
+          target,
+          groups: kept,
+          kind: CollisionKind::Exists,
+        });
+      }
+    }
+    collisions
+  }
+
+  /// Checks the inputs can be read, and the output directories can take the outputs
+  fn preflight(&self, options: &Options, skipped: Option<&BTreeSet<String>>) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut needed: BTreeMap<std::path::PathBuf, u64> = BTreeMap::new();
+    for (key, group) in self.converted_groups(skipped) {
+      problems.extend(
+        group
+          .inputs()
+          .filter_map(|path| preflight::check_readable(key, path).err()),
+      );
+      // groups whose outputs are unknown fail later with their own error
+      if let (Some(dir), Ok(size)) = (group.output_dir(options), group.output_size(options)) {
+        // the staged and the temporary copy of each output exist at the same time
+        *needed.entry(dir).or_default() += 2 * size;
+      }
+    }
+    for (dir, needed) in needed {
+      match preflight::check_writable(&dir) {
+        Ok(()) => problems.extend(preflight::check_space(&dir, needed).err()),
+        Err(problem) => problems.push(problem),
+      }
+    }
+    problems
+  }
+
+  /// Converts every group, except the skipped ones.
+  ///
+  /// The inputs and outputs of the converted groups are checked first, and nothing is written if
+  /// there are problems or outputs collide. Existing outputs are not flagged again once the user
+  /// chose to convert their groups anyway.
   pub(crate) fn convert(
     &mut self,
     options: &Options,
     errors: &mut ErrorList<ErrorCategory>,
-  ) -> Vec<String> {
-    let mut converted_successfully = Vec::with_capacity(self.groups.len());
+    skipped: Option<&BTreeSet<String>>,
+    check_existing: bool,
+  ) -> ConvertStatus {
+    self.conversion = None;
+    self.detect_byte_orders();
+    let problems = self.preflight(options, skipped);
+    if !problems.is_empty() {
+      return ConvertStatus::Problems(problems);
+    }
+    let collisions = self
+      .collisions(options, skipped)
+      .into_iter()
+      .filter(|c| check_existing || c.kind == CollisionKind::Shared)
+      .collect::<Vec<_>>();
+    if !collisions.is_empty() {
+      return ConvertStatus::Collisions(collisions);
+    }
+
+    let mut staged = Vec::with_capacity(self.groups.len());
+    let mut failed = Vec::new();
     self.selection = None;
     for (key, group) in std::mem::take(&mut self.groups) {
-      if let Err((error, group)) = group.convert(options) {
-        self.groups.insert(key.clone(), group);
-        errors.add(
-          ErrorCategory::Conversion,
-          ItemConversionError { group: key, error },
-        );
-      } else {
-        converted_successfully.push(key)
+      if skipped.is_some_and(|s| s.contains(&key)) {
+        self.groups.insert(key, group);
+        continue;
+      }
+      match group.stage(options) {
+        Ok((files, group)) => {
+          self.groups.insert(key.clone(), group);
+          staged.push((key, files));
+        }
+        Err((error, group)) => {
+          failed.push(GroupRun {
+            group: key.clone(),
+            inputs: group.inputs().cloned().collect(),
+            outputs: Vec::new(),
+            outcome: Outcome::Failed(error.to_string()),
+          });
+          self.groups.insert(key.clone(), group);
+          let error = error.in_group(key);
+          errors.add(ErrorCategory::of(&error, ErrorCategory::Conversion), error);
+        }
       }
     }
-    converted_successfully
+    self.conversion = Some(Conversion {
+      staged,
+      apply_to_all: None,
+      failed,
+    });
+    self.continue_conversion(errors)
   }
 
-  pub(crate) fn are_all_valid(&self) -> bool {
-    !self.groups.is_empty() && self.groups.values().all(GroupItem::is_valid)
+  /// Takes the decision for the file last asked about, and continues the conversion
+  pub(crate) fn answer(
+    &mut self,
+    decision: Decision,
+    apply_to_all: bool,
+    errors: &mut ErrorList<ErrorCategory>,
+  ) -> ConvertStatus {
+    if let Some(conversion) = &mut self.conversion {
+      if let Some(placement) = conversion.undecided().next() {
+        placement.decision = Some(decision);
+      }
+      if apply_to_all {
+        conversion.apply_to_all = Some(decision);
+      }
+    }
+    self.continue_conversion(errors)
   }
-}
 
-#[derive(Debug)]
-struct ItemConversionError {
-  group: String,
-  error: Box<dyn std::error::Error>,
-}
-impl std::fmt::Display for ItemConversionError {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    f.write_fmt(format_args!("{}: {}", self.group, self.error))
+  /// Checks if a conversion waits for the user to decide about existing files
+  pub(crate) fn is_converting(&self) -> bool {
+    self.conversion.is_some()
+  }
+
+  /// Drops a conversion waiting for an answer, without placing any file
+  pub(crate) fn cancel_conversion(&mut self) {
+    self.conversion = None;
+  }
+
+  fn continue_conversion(&mut self, errors: &mut ErrorList<ErrorCategory>) -> ConvertStatus {
+    let Some(mut conversion) = self.conversion.take() else {
+      return ConvertStatus::Done(Converted::default());
+    };
+
+    if let Some(decision) = conversion.apply_to_all {
+      for placement in conversion.undecided() {
+        placement.decision = Some(decision);
+      }
+    }
+    let question = conversion.staged.iter().find_map(|(key, staged)| {
+      let placement = staged.placements.iter().find(|p| p.decision.is_none())?;
+      Some(Question::new(
+        key.clone(),
+        &placement.staged,
+        &placement.target,
+      ))
+    });
+    if let Some(question) = question {
+      self.conversion = Some(conversion);
+      return ConvertStatus::Ask(question);
+    }
+
+    let mut result = Converted {
+      runs: conversion.failed,
+      ..Default::default()
+    };
+    for (key, staged) in conversion.staged {
+      let inputs = self
+        .groups
+        .get(&key)
+        .map(|group| group.inputs().cloned().collect())
+        .unwrap_or_default();
+      let existing = staged
+        .placements
+        .iter()
+        .filter(|p| p.unchanged)
+        .map(|p| p.target.clone())
+        .collect::<Vec<_>>();
+      let unchanged =
+        !staged.placements.is_empty() && staged.placements.iter().all(|p| p.unchanged);
+      let all_skipped = !staged.placements.is_empty()
+        && staged
+          .placements
+          .iter()
+          .all(|p| p.decision == Some(Decision::Skip));
+      let (mut outputs, outcome) = match staged.place() {
+        Ok(placed) => (placed, None),
+        Err(PlaceError {
+          error,
+          placed,
+          leftovers,
+        }) => {
+          for leftover in leftovers {
+            let error = AppError::from(leftover);
+            errors.add(ErrorCategory::of(&error, ErrorCategory::Output), error);
+          }
+          let outcome = error.map(|error| {
+            let outcome = Outcome::Failed(error.to_string());
+            let error = AppError::from(error).in_group(key.clone());
+            errors.add(ErrorCategory::of(&error, ErrorCategory::Output), error);
+            outcome
+          });
+          (placed, outcome)
+        }
+      };
+      let outcome = match outcome {
+        Some(failed) => failed,
+        None if unchanged => {
+          self.groups.remove(&key);
+          result.unchanged.push(key.clone());
+          Outcome::Unchanged
+        }
+        None if all_skipped => Outcome::Skipped,
+        None => {
+          self.groups.remove(&key);
+          result.converted.push(key.clone());
+          Outcome::Converted
+        }
+      };
+      outputs.extend(existing);
+      result.runs.push(GroupRun {
+        group: key,
+        inputs,
+        outputs,
+        outcome,
+      });
+    }
+    ConvertStatus::Done(result)
+  }
+
+  pub(crate) fn get_last_action(&mut self) -> Option<ItemAction> {
+    self.last_action.take()
+  }
+
+  pub(crate) fn pack_files(&self) -> Vec<std::path::PathBuf> {
+    self
+      .groups
+      .values()
+      .flat_map(GroupItem::pack_files)
+      .cloned()
+      .collect()
+  }
+
+  /// Gets the files to detect the byte order of each group from, for the groups which need it
+  pub(crate) fn take_byte_order_probes(&mut self) -> Vec<(String, Probe)> {
+    self
+      .groups
+      .iter_mut()
+      .filter_map(|(key, group)| Some((key.clone(), group.take_probe()?)))
+      .collect()
+  }
+
+  /// Sets the byte orders detected by the worker
+  pub(crate) fn set_byte_orders(&mut self, detected: Vec<(String, Probe, Option<Detection>)>) {
+    for (key, probe, detection) in detected {
+      if let Some(group) = self.groups.get_mut(&key) {
+        group.set_byte_order(probe, detection);
+      }
+    }
+  }
+
+  /// Detects the byte orders the worker did not detect yet, before they are needed
+  pub(crate) fn detect_byte_orders(&mut self) {
+    self
+      .groups
+      .values_mut()
+      .for_each(GroupItem::detect_byte_order);
+  }
+
+  /// Lists the groups converted against their detected byte order, when the detected orders disagree
+  pub(crate) fn byte_order_conflicts(&self, options: &Options) -> Vec<String> {
+    let detected = self.groups.values().filter_map(GroupItem::byte_order);
+    let swapped = detected.clone().filter(|d| d.swap_bytes()).count();
+    if swapped == 0 || swapped == detected.count() {
+      return Vec::new();
+    }
+    self
+      .groups
+      .iter()
+      .filter_map(|(key, group)| {
+        let detection = group.byte_order()?;
+        (detection.swap_bytes() != group.swap_bytes(options)).then(|| {
+          format!(
+            "{key}: detected {detection}, but will be converted {}",
+            if group.swap_bytes(options) {
+              "swapped"
+            } else {
+              "unswapped"
+            }
+          )
+        })
+      })
+      .collect()
+  }
+
+  /// Sets the swap bytes of every group with a detected byte order to match it
+  pub(crate) fn apply_detected_byte_order(&mut self) {
+    for group in self.groups.values_mut() {
+      if let Some(detection) = group.byte_order() {
+        group.overrides.swap_bytes = Some(detection.swap_bytes());
+      }
+    }
+  }
+
+  pub(crate) fn are_all_valid(&self) -> bool {
+    !self.groups.is_empty() && self.groups.values().all(GroupItem::is_valid)
   }
 }
-impl std::error::Error for ItemConversionError {}
 
 macro_rules! pick_file {
-  ($ui:expr, $paths:expr, $label:ident) => {{
-    pick_file!($ui, $paths, $label, paste! {stringify!([<$label:upper>])})
+  ($ui:expr, $paths:expr, $label:ident, $view:expr) => {{
+    pick_file!(
+      $ui,
+      $paths,
+      $label,
+      paste! {stringify!([<$label:upper>])},
+      $view
+    )
   }};
-  ($ui:expr, $paths:expr, $file:ident, $label:expr) => {{
+  ($ui:expr, $paths:expr, $file:ident, $label:expr, $view:expr) => {{
     $ui.label($label);
     let enabled = paste! { $paths.[<is_ $file _enabled>]() };
     let valid = paste! { $paths.[<is_ $file _valid>]() };
     let mut path = $paths.$file().clone();
-    if pick_file!($ui, enabled, valid, &mut path, $label, &[stringify!($file)]) {
+    let changed = pick_file!($ui, enabled, valid, &mut path, $label, &[stringify!($file)]);
+    $ui.add_enabled_ui(path.as_ref().is_some_and(|p| p.is_file()), |ui| {
+      if ui.small_button("🔍").on_hover_text("View Hex").clicked() {
+        $view = path.clone();
+      }
+    });
+    $ui.end_row();
+    if changed {
       if let Some(path) = path {
         $paths.set(path);
       }
@@ -159,11 +629,52 @@ macro_rules! pick_file {
       )
       .changed()
     });
-    $ui.end_row();
     changed.inner
   }};
 }
 
+/// Shows a default/yes/no choice for an overridable option
+fn override_option(ui: &mut egui::Ui, label: &str, value: &mut Option<bool>) -> bool {
+  const CHOICES: [Option<bool>; 3] = [None, Some(true), Some(false)];
+
+  ui.label(label);
+  let mut index = CHOICES.iter().position(|c| c == value).unwrap();
+  let changed = ui
+    .with_layout(ui.layout().with_main_justify(true), |ui| {
+      egui::ComboBox::from_id_source(("group_override", label))
+        .show_index(ui, &mut index, CHOICES.len(), |i| {
+          match CHOICES[i] {
+            None => "Default",
+            Some(true) => "Yes",
+            Some(false) => "No",
+          }
+          .to_string()
+        })
+        .changed()
+    })
+    .inner;
+  ui.end_row();
+  *value = CHOICES[index];
+  changed
+}
+
+/// Group name in the list, marked when the group overrides some option
+#[derive(Clone, Copy)]
+struct GroupLabel<'a> {
+  key: &'a String,
+  overridden: bool,
+}
+
+impl From<GroupLabel<'_>> for egui::WidgetText {
+  fn from(label: GroupLabel<'_>) -> Self {
+    if label.overridden {
+      format!("{} ⚙", label.key).into()
+    } else {
+      label.key.as_str().into()
+    }
+  }
+}
+
 impl FileGroups {
   pub fn show_filtered<F>(&mut self, ui: &mut egui::Ui, filter: F)
   where
@@ -190,6 +701,7 @@ impl FileGroups {
     }
 
     let mut item_updated = false;
+    let mut view = None;
 
     egui::SidePanel::new(egui::panel::Side::Right, "item options")
       .min_width(150.0)
@@ -206,9 +718,15 @@ impl FileGroups {
             ui.small("Group");
             ui.trim_label(group_name, false);
           });
+          if let Some(folder) = entry.relative_dir().filter(|d| !d.as_os_str().is_empty()) {
+            ui.horizontal(|ui| {
+              ui.small("Folder");
+              ui.trim_label(folder.to_string_lossy(), false);
+            });
+          }
           ui.vertical(|ui| ui.add_space(3.0));
           egui::Grid::new("group_file_main")
-            .num_columns(2)
+            .num_columns(3)
             .show(ui, |ui| {
               ui.label("Mode");
               ui.with_layout(ui.layout().with_main_justify(true), |ui| {
@@ -225,37 +743,119 @@ impl FileGroups {
                 }
               });
               ui.end_row();
-              item_updated |= pick_file!(ui, entry, srm);
+              item_updated |= pick_file!(ui, entry, srm, view);
+              if let Some(srm) = entry.srm().as_ref().filter(|p| p.is_file()) {
+                ui.label("");
+                if ui.small_button("Show Layout").clicked() {
+                  self.last_action = Some(ItemAction::ShowLayout(srm.clone()));
+                }
+                ui.end_row();
+              }
             });
 
           ui.vertical(|ui| ui.add_space(3.0));
           ui.small("Battery File (Only One)");
           egui::Grid::new("group_file_battery")
-            .num_columns(2)
+            .num_columns(3)
             .show(ui, |ui| {
-              item_updated |= pick_file!(ui, entry, eep);
-              item_updated |= pick_file!(ui, entry, sra);
-              item_updated |= pick_file!(ui, entry, fla);
+              item_updated |= pick_file!(ui, entry, eep, view);
+              item_updated |= pick_file!(ui, entry, sra, view);
+              item_updated |= pick_file!(ui, entry, fla, view);
+              if entry.eep().is_some() || entry.fla().is_some() {
+                ui.label("Order");
+                match entry.byte_order() {
+                  Some(detection) => ui.label(detection.to_string()),
+                  None => ui.weak("Unknown"),
+                };
+                if let Some(detection) = entry.byte_order() {
+                  if entry.overrides.swap_bytes != Some(detection.swap_bytes())
+                    && ui
+                      .small_button("Apply")
+                      .on_hover_text("Override Swap Bytes with the detected byte order")
+                      .clicked()
+                  {
+                    entry.overrides.swap_bytes = Some(detection.swap_bytes());
+                    item_updated = true;
+                  }
+                }
+                ui.end_row();
+              }
             });
 
           ui.vertical(|ui| ui.add_space(3.0));
           ui.small("Controller Packs (Mupen or Players)");
           egui::Grid::new("group_file_cp")
-            .num_columns(2)
+            .num_columns(3)
+            .show(ui, |ui| {
+              item_updated |= pick_file!(ui, entry, mpk, "Mupen", view);
+              item_updated |= pick_file!(ui, entry, mpk1, "Player 1", view);
+              item_updated |= pick_file!(ui, entry, mpk2, "Player 2", view);
+              item_updated |= pick_file!(ui, entry, mpk3, "Player 3", view);
+              item_updated |= pick_file!(ui, entry, mpk4, "Player 4", view);
+            });
+
+          ui.vertical(|ui| ui.add_space(3.0));
+          ui.small("Overrides");
+          egui::Grid::new("group_overrides")
+            .num_columns(3)
             .show(ui, |ui| {
-              item_updated |= pick_file!(ui, entry, mpk, "Mupen");
-              item_updated |= pick_file!(ui, entry, mpk1, "Player 1");
-              item_updated |= pick_file!(ui, entry, mpk2, "Player 2");
-              item_updated |= pick_file!(ui, entry, mpk3, "Player 3");
-              item_updated |= pick_file!(ui, entry, mpk4, "Player 4");
+              let overrides = &mut entry.overrides;
+              item_updated |= override_option(ui, "Overwrite", &mut overrides.overwrite);
+              item_updated |= override_option(ui, "Swap Bytes", &mut overrides.swap_bytes);
+              item_updated |= override_option(ui, "Mupen Pack", &mut overrides.output_mupen);
+
+              ui.label("Output");
+              ui.with_layout(ui.layout().with_main_justify(true), |ui| {
+                item_updated |= ui
+                  .browse(
+                    &mut overrides.output_dir,
+                    Browse::pick_directory().set_default_text("Default"),
+                  )
+                  .changed();
+              });
+              if overrides.output_dir.is_some()
+                && ui.small_button("✖").on_hover_text("Use Default").clicked()
+              {
+                overrides.output_dir = None;
+                item_updated = true;
+              }
+              ui.end_row();
             });
         },
       );
 
-    ItemList::new(self.groups.keys(), "entries")
+    if let Some(path) = view {
+      self.last_action = Some(ItemAction::ShowHex(path));
+    }
+
+    let labels = self
+      .groups
+      .iter()
+      .map(|(key, group)| GroupLabel {
+        key,
+        overridden: !group.overrides.is_empty(),
+      })
+      .collect::<Vec<_>>();
+    ItemList::new(labels.iter().copied(), "entries")
       .with_tooltips(false)
-      .with_validation(|key: &&String| self.groups[*key].is_valid())
+      .with_validation(|label: &GroupLabel| self.groups[label.key].is_valid())
       .show(&mut self.selection, ui);
     item_updated
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn group_files_by_folder() {
+    let mut groups = FileGroups::default();
+    assert_eq!(groups.add_file("a/Zelda.srm".into()), "Zelda");
+    assert_eq!(groups.add_file("b/Zelda.srm".into()), "Zelda (b)");
+    assert_eq!(groups.add_file("a/Zelda.eep".into()), "Zelda");
+    assert_eq!(groups.add_file("c/b/Zelda.eep".into()), "Zelda (c/b)");
+    assert_eq!(groups.add_file("b/Zelda.eep".into()), "Zelda (b)");
+    assert_eq!(groups.len(), 3);
+  }
+}
@@ -1,13 +1,45 @@
-use crate::app::options::Options;
+use crate::app::{
+  byte_order::{Detection, Probe},
+  error::AppError,
+  options::Options,
+  output::{self, Decision, Destination, Placement, Staged, Staging},
+  srm::{self, Region},
+};
 
 use paste::paste;
 
-use ramp64_srm_convert_lib::{BatteryPath, ControllerPackPaths, Converter};
+use ramp64_srm_convert_lib::{BatteryPath, ControllerPackPaths, Converter, UserParams};
 
 #[derive(Debug)]
 pub(crate) struct GroupItem {
   pub(super) way: Way,
   paths: Box<Paths>,
+  pub(super) overrides: Overrides,
+  byte_order: Option<Detection>,
+  /// The files the byte order was detected from
+  detected_from: Option<Box<Probe>>,
+  /// The files sent to the worker to detect the byte order from
+  detecting: Option<Box<Probe>>,
+  /// Directory scanned to find the files of this group
+  pub(super) scan_root: Option<std::path::PathBuf>,
+}
+
+/// Options set for a single group, replacing the global ones
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Overrides {
+  pub overwrite: Option<bool>,
+  pub swap_bytes: Option<bool>,
+  pub output_mupen: Option<bool>,
+  pub output_dir: Option<std::path::PathBuf>,
+}
+
+impl Overrides {
+  pub(crate) fn is_empty(&self) -> bool {
+    self.overwrite.is_none()
+      && self.swap_bytes.is_none()
+      && self.output_mupen.is_none()
+      && self.output_dir.is_none()
+  }
 }
 
 macro_rules! can_edit {
@@ -82,6 +114,11 @@ impl GroupItem {
     Self {
       way: Way::Create,
       paths,
+      overrides: Overrides::default(),
+      byte_order: None,
+      detected_from: None,
+      detecting: None,
+      scan_root: None,
     }
   }
 
@@ -89,6 +126,11 @@ impl GroupItem {
     Self {
       way: Way::Split,
       paths,
+      overrides: Overrides::default(),
+      byte_order: None,
+      detected_from: None,
+      detecting: None,
+      scan_root: None,
     }
   }
 
@@ -105,15 +147,240 @@ impl GroupItem {
   }
 
   pub(crate) fn set(&mut self, path: std::path::PathBuf) {
-    self.paths.set(path)
+    self.paths.set(path);
+  }
+
+  /// Gets the EEP or FLA file of this group, with the SRM to compare it with
+  fn probe(&self) -> Option<Probe> {
+    let (path, region) = match (&self.paths.eep, &self.paths.fla) {
+      (Some(eep), _) => (eep, Region::Eeprom),
+      (_, Some(fla)) => (fla, Region::FlashRam),
+      _ => return None,
+    };
+    Some(Probe {
+      path: path.clone(),
+      region,
+      srm: self.paths.srm.clone(),
+    })
+  }
+
+  /// Gets the files to detect the byte order from, once, when they changed since the last detection
+  pub(crate) fn take_probe(&mut self) -> Option<Probe> {
+    let probe = self.probe()?;
+    if self.detected_from.as_deref() == Some(&probe) || self.detecting.as_deref() == Some(&probe) {
+      return None;
+    }
+    self.detecting = Some(probe.clone().into());
+    Some(probe)
+  }
+
+  /// Sets the byte order detected from the files, unless they changed since
+  pub(crate) fn set_byte_order(&mut self, probe: Probe, detection: Option<Detection>) {
+    if self.probe().as_ref() == Some(&probe) {
+      self.byte_order = detection;
+      self.detected_from = Some(probe.into());
+    }
+  }
+
+  /// Detects the byte order right away, if it was not detected from the current files
+  pub(crate) fn detect_byte_order(&mut self) {
+    if let Some(probe) = self.probe() {
+      if self.detected_from.as_deref() != Some(&probe) {
+        let detection = probe.detect();
+        self.set_byte_order(probe, detection);
+      }
+    }
+  }
+
+  /// Gets the detected byte order of the EEP or FLA file of this group
+  pub(crate) fn byte_order(&self) -> Option<Detection> {
+    self.byte_order
+  }
+
+  /// Gets if the EEP/FlashRAM data of this group will be word-swapped
+  pub(crate) fn swap_bytes(&self, options: &Options) -> bool {
+    self
+      .overrides
+      .swap_bytes
+      .or(
+        self
+          .byte_order
+          .filter(|_| options.auto_byte_order)
+          .map(|d| d.swap_bytes()),
+      )
+      .unwrap_or(options.user_params.swap_bytes)
   }
 
-  pub(crate) fn convert(self, options: &Options) -> Result<(), (Box<dyn std::error::Error>, Self)> {
+  /// Gets the existing files of this group which contain controller packs
+  pub(crate) fn pack_files(&self) -> impl Iterator<Item = &std::path::PathBuf> {
+    let Paths {
+      srm,
+      mpk,
+      mpk1,
+      mpk2,
+      mpk3,
+      mpk4,
+      ..
+    } = self.paths.as_ref();
+    [srm, mpk, mpk1, mpk2, mpk3, mpk4]
+      .into_iter()
+      .filter_map(Option::as_ref)
+      .filter(|p| p.is_file())
+  }
+
+  /// Gets the folder holding the files of this group
+  pub(crate) fn folder(&self) -> Option<&std::path::Path> {
+    self.paths.srm.iter().chain(self.paths.natives()).next()?.parent()
+  }
+
+  /// Gets the file the outputs of this group are named after
+  fn main_path(&self) -> Option<&std::path::PathBuf> {
+    match self.way {
+      Way::Create => self.paths.main_path(),
+      Way::Split => self.paths.srm.as_ref(),
+    }
+  }
+
+  /// Gets the files read by the conversion of this group
+  pub(crate) fn inputs(&self) -> Box<dyn Iterator<Item = &std::path::PathBuf> + '_> {
     match self.way {
-      Way::Create => create_conversion(self.paths, options),
-      Way::Split => split_conversion(self.paths, options),
+      Way::Create => Box::new(self.paths.natives()),
+      Way::Split => Box::new(self.paths.srm.iter()),
     }
   }
+
+  /// Gets the directory the outputs of this group are written into
+  pub(crate) fn output_dir(&self, options: &Options) -> Option<std::path::PathBuf> {
+    self
+      .destination(&self.options(options))
+      .map(|destination| destination.out_dir)
+  }
+
+  /// Gets the folder of this group relative to the scanned directory
+  pub(crate) fn relative_dir(&self) -> Option<&std::path::Path> {
+    let main_path = self.main_path()?;
+    main_path
+      .parent()?
+      .strip_prefix(self.scan_root.as_ref()?)
+      .ok()
+  }
+
+  /// Gets where the outputs are placed, using the already merged options
+  fn destination(&self, options: &Options) -> Option<Destination> {
+    let sub_dir = match options.output_dir {
+      Some(_) if options.mirror_dirs => self.relative_dir().map(std::path::Path::to_path_buf),
+      _ => None,
+    };
+    Some(Destination::new(
+      self.main_path()?,
+      options.output_dir.as_deref(),
+      sub_dir.unwrap_or_default(),
+    ))
+  }
+
+  /// Gets the types of the files the conversion of this group will write
+  fn output_kinds(&self, options: &Options) -> Result<Vec<String>, AppError> {
+    match self.way {
+      Way::Create => Ok(vec![String::from("srm")]),
+      Way::Split => {
+        // the conversion fails later, as there is nothing to split
+        let Some(srm_path) = &self.paths.srm else {
+          return Ok(Vec::new());
+        };
+        let data = srm::read(srm_path).map_err(|e| AppError::io(e, srm_path))?;
+        let mut kinds = Vec::new();
+        for info in srm::analyze(&data).iter().filter(|i| i.split_output) {
+          let kind = info.region.extension(options.output_mupen);
+          if !kinds.contains(&kind) {
+            kinds.push(kind);
+          }
+        }
+        Ok(kinds)
+      }
+    }
+  }
+
+  /// Gets the paths of every file the conversion of this group will write
+  pub(crate) fn targets(&self, options: &Options) -> Result<Vec<std::path::PathBuf>, AppError> {
+    let options = self.options(options);
+    let Some(destination) = self.destination(&options) else {
+      return Ok(Vec::new());
+    };
+    self
+      .output_kinds(&options)?
+      .iter()
+      .map(|kind| Ok(destination.target(&options.name_template, kind)?))
+      .collect()
+  }
+
+  /// Gets the total size of the files the conversion of this group will write
+  pub(crate) fn output_size(&self, options: &Options) -> Result<u64, AppError> {
+    let options = self.options(options);
+    Ok(
+      self
+        .output_kinds(&options)?
+        .iter()
+        .map(|kind| srm::output_size(kind))
+        .sum(),
+    )
+  }
+
+  /// Merges the overrides of this group with the global options
+  pub(crate) fn options(&self, options: &Options) -> Options {
+    let overrides = &self.overrides;
+    Options {
+      user_params: UserParams {
+        overwrite: overrides.overwrite.unwrap_or(options.user_params.overwrite),
+        swap_bytes: self.swap_bytes(options),
+      },
+      ask_overwrite: overrides.overwrite.is_none() && options.ask_overwrite,
+      output_mupen: overrides.output_mupen.unwrap_or(options.output_mupen),
+      output_dir: overrides
+        .output_dir
+        .clone()
+        .or_else(|| options.output_dir.clone()),
+      ..options.clone()
+    }
+  }
+
+  /// Converts the group into a staging directory, ready for its files to be placed
+  pub(crate) fn stage(self, options: &Options) -> Result<(Staged, Self), (Box<AppError>, Self)> {
+    let options = self.options(options);
+    let Some(destination) = self.destination(&options) else {
+      let error = AppError::validation("the group has no files to convert");
+      return Err((error.into(), self));
+    };
+    let (overrides, byte_order, scan_root) = (self.overrides, self.byte_order, self.scan_root);
+    let detected_from = self.detected_from;
+    match self.way {
+      Way::Create => create_conversion(self.paths, &options, destination),
+      Way::Split => split_conversion(self.paths, &options, destination),
+    }
+    .map(|(staged, item)| {
+      (
+        staged,
+        Self {
+          overrides: overrides.clone(),
+          byte_order,
+          detected_from: detected_from.clone(),
+          scan_root: scan_root.clone(),
+          ..item
+        },
+      )
+    })
+    .map_err(|(e, item)| {
+      (
+        e,
+        Self {
+          overrides,
+          byte_order,
+          detected_from,
+          scan_root,
+          ..item
+        },
+      )
+    })
+  }
 }
 
 impl From<std::path::PathBuf> for GroupItem {
@@ -125,32 +392,18 @@ impl From<std::path::PathBuf> for GroupItem {
     };
     let mut paths = Box::from(Paths::default());
     paths.set(path);
-    Self { way, paths }
-  }
-}
-
-#[derive(Debug)]
-pub(crate) struct InvalidGroupError<V>
-where
-  V: std::fmt::Display + std::fmt::Debug,
-{
-  validation: Option<V>,
-}
-
-impl<V> std::fmt::Display for InvalidGroupError<V>
-where
-  V: std::fmt::Display + std::fmt::Debug,
-{
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    match &self.validation {
-      Some(validation) => f.write_fmt(format_args!("{validation}")),
-      None => f.write_str("An invalid group was being converted!"),
+    Self {
+      way,
+      paths,
+      overrides: Overrides::default(),
+      byte_order: None,
+      detected_from: None,
+      detecting: None,
+      scan_root: None,
     }
   }
 }
 
-impl<V> std::error::Error for InvalidGroupError<V> where V: std::fmt::Display + std::fmt::Debug {}
-
 fn to_battery(path: std::path::PathBuf) -> Option<BatteryPath> {
   ramp64_srm_convert_lib::to_battery(path).ok()
 }
@@ -162,7 +415,8 @@ fn to_controller_pack(path: std::path::PathBuf) -> Option<ControllerPackPaths> {
 fn create_conversion(
   paths: Box<Paths>,
   options: &Options,
-) -> Result<(), (Box<dyn std::error::Error>, GroupItem)> {
+  destination: Destination,
+) -> Result<(Staged, GroupItem), (Box<AppError>, GroupItem)> {
   use ramp64_srm_convert_lib::create::Params;
 
   let Paths {
@@ -197,42 +451,40 @@ fn create_conversion(
 
   let validation = params.validate();
   if !validation.is_ok() {
-    return Err((
-      Box::new(InvalidGroupError {
-        validation: Some(validation),
-      }),
-      GroupItem::create(paths),
-    ));
+    return Err((AppError::validation(validation).into(), GroupItem::create(paths)));
   }
 
-  params
-    .convert(&options.user_params)
-    .map_err(|e| (Box::from(e), GroupItem::create(paths)))
-}
-
-#[derive(Debug)]
-struct InvalidSrmError {}
-impl std::fmt::Display for InvalidSrmError {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    f.write_str("Invalid SRM for Split in group")
+  let modified = output::newest_modified(paths.natives());
+  match stage_outputs(
+    destination,
+    options,
+    true,
+    modified,
+    |staging, user_params| params.set_out_dir(Some(staging)).convert(user_params),
+  ) {
+    Ok(staged) => Ok((staged, GroupItem::create(paths))),
+    Err(e) => Err((e.into(), GroupItem::create(paths))),
   }
 }
-impl std::error::Error for InvalidSrmError {}
 
 fn split_conversion(
   paths: Box<Paths>,
   options: &Options,
-) -> Result<(), (Box<dyn std::error::Error>, GroupItem)> {
+  destination: Destination,
+) -> Result<(Staged, GroupItem), (Box<AppError>, GroupItem)> {
   use ramp64_srm_convert_lib::split::{can_be_srm, Params};
 
   let srm = paths.srm.clone();
   let Some(srm_path) = srm else {
-    return Err((Box::from(InvalidGroupError {validation:None::<&str>}), GroupItem::split(paths)))
+    let error = AppError::validation("the group has no SRM file to split");
+    return Err((error.into(), GroupItem::split(paths)));
   };
 
   let srm_path = match can_be_srm(srm_path) {
     Ok(path) => path,
-    Err((_, err)) => return Err((Box::from(err), GroupItem::split(paths))),
+    Err((path, err)) => {
+      return Err((AppError::from(err).at(path).into(), GroupItem::split(paths)))
+    }
   };
 
   let params = Params::new(srm_path)
@@ -241,20 +493,94 @@ fn split_conversion(
 
   let validation = params.validate();
   if !validation.is_ok() {
-    return Err((
-      Box::from(InvalidGroupError {
-        validation: Some(validation),
-      }),
-      GroupItem::split(paths),
-    ));
+    return Err((AppError::validation(validation).into(), GroupItem::split(paths)));
   }
 
-  params
-    .convert(&options.user_params)
-    .map_err(|e| (Box::from(e), GroupItem::split(paths)))
+  let modified = output::newest_modified(&paths.srm);
+  match stage_outputs(
+    destination,
+    options,
+    false,
+    modified,
+    |staging, user_params| params.set_out_dir(Some(staging)).convert(user_params),
+  ) {
+    Ok(staged) => Ok((staged, GroupItem::split(paths))),
+    Err(e) => Err((e.into(), GroupItem::split(paths))),
+  }
+}
+
+/// Converts into a staging directory, and finds where each written file goes as the naming
+/// template says
+fn stage_outputs<F>(
+  destination: Destination,
+  options: &Options,
+  merge_srm: bool,
+  modified: Option<std::time::SystemTime>,
+  convert: F,
+) -> Result<Staged, AppError>
+where
+  F: FnOnce(&std::path::Path, &UserParams) -> Result<(), ramp64_srm_convert_lib::Error>,
+{
+  let out_dir = &destination.out_dir;
+  let staging = Staging::new(out_dir).map_err(|e| AppError::io(e, out_dir))?;
+  if merge_srm {
+    // create merges the new data into the existing SRM
+    let target = destination.target(&options.name_template, "srm")?;
+    if target.is_file() {
+      std::fs::copy(&target, staging.path().join(destination.staged_name("srm")))
+        .map_err(|e| AppError::io(e, target))?;
+    }
+  }
+
+  let user_params = UserParams {
+    overwrite: true,
+    ..options.user_params
+  };
+  convert(staging.path(), &user_params)?;
+
+  let mut placements = Vec::new();
+  for (staged, kind) in staging.files()? {
+    let target = destination.target(&options.name_template, &kind)?;
+    let unchanged =
+      options.skip_identical && target.is_file() && output::same_contents(&staged, &target)?;
+    let newer = options.skip_newer
+      && modified
+        .is_some_and(|input| output::newest_modified([&target]).is_some_and(|t| t > input));
+    let decision = if unchanged || newer {
+      Some(Decision::Skip)
+    } else if options.user_params.overwrite || !target.exists() {
+      Some(Decision::Overwrite)
+    } else if options.ask_overwrite {
+      None
+    } else {
+      return Err(AppError::io(std::io::ErrorKind::AlreadyExists.into(), target));
+    };
+    placements.push(Placement {
+      staged,
+      target,
+      decision,
+      unchanged,
+    });
+  }
+  let modified = modified.filter(|_| options.preserve_times);
+  Ok(Staged::new(staging, placements, modified))
 }
 
 impl Paths {
+  /// Gets the file the created SRM is named after
+  fn main_path(&self) -> Option<&std::path::PathBuf> {
+    self.natives().next()
+  }
+
+  /// Gets every native save file of the group
+  fn natives(&self) -> impl Iterator<Item = &std::path::PathBuf> {
+    [
+      &self.eep, &self.sra, &self.fla, &self.mpk, &self.mpk1, &self.mpk2, &self.mpk3, &self.mpk4,
+    ]
+    .into_iter()
+    .filter_map(Option::as_ref)
+  }
+
   pub(crate) fn set(&mut self, path: std::path::PathBuf) {
     use Tag::*;
     match tag_path(&path) {
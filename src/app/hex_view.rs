@@ -0,0 +1,299 @@
+use super::{srm, work::GenericError};
+
+use egui::text::LayoutJob;
+use egui::{Color32, TextFormat, TextStyle};
+
+use std::ops::Range;
+
+const BYTES_PER_ROW: usize = 16;
+/// Limits how many search matches are kept
+const MAX_MATCHES: usize = 10_000;
+
+#[derive(Debug, Default)]
+pub(crate) struct HexView {
+  path: std::path::PathBuf,
+  data: Vec<u8>,
+  error: Option<GenericError>,
+  is_srm: bool,
+  jump_to: String,
+  search: String,
+  search_hex: bool,
+  matches: Vec<usize>,
+  match_len: usize,
+  current_match: usize,
+  highlight: Option<Range<usize>>,
+  scroll_to: Option<usize>,
+  message: Option<String>,
+}
+
+fn parse_offset(text: &str) -> Option<usize> {
+  let text = text.trim();
+  let text = text
+    .strip_prefix("0x")
+    .or_else(|| text.strip_prefix("0X"))
+    .unwrap_or(text);
+  if !text.chars().all(|c| c.is_ascii_hexdigit()) {
+    return None;
+  }
+  usize::from_str_radix(text, 16).ok()
+}
+
+fn parse_hex_bytes(text: &str) -> Option<Vec<u8>> {
+  let digits = text
+    .chars()
+    .filter(|c| !c.is_whitespace())
+    .collect::<Vec<_>>();
+  if digits.len() % 2 != 0 || !digits.iter().all(char::is_ascii_hexdigit) {
+    return None;
+  }
+  digits
+    .chunks(2)
+    .map(|pair| u8::from_str_radix(&pair.iter().collect::<String>(), 16).ok())
+    .collect()
+}
+
+fn find_all(data: &[u8], needle: &[u8]) -> Vec<usize> {
+  if needle.is_empty() || needle.len() > data.len() {
+    return Vec::new();
+  }
+  data
+    .windows(needle.len())
+    .enumerate()
+    .filter_map(|(i, w)| (w == needle).then_some(i))
+    .take(MAX_MATCHES)
+    .collect()
+}
+
+impl HexView {
+  pub fn new(path: std::path::PathBuf) -> Self {
+    let has_srm_extension = path
+      .extension()
+      .is_some_and(|e| e.eq_ignore_ascii_case("srm"));
+    // a broken SRM is still shown, only without its regions
+    let srm_data = has_srm_extension.then(|| srm::read(&path).ok()).flatten();
+    let is_srm = srm_data.is_some();
+    let data = srm_data.map_or_else(|| std::fs::read(&path), Ok);
+    let (data, error) = match data {
+      Ok(data) => (data, None),
+      Err(e) => (Vec::new(), Some(GenericError::new(e, path.clone()))),
+    };
+    let message = (has_srm_extension && !is_srm && error.is_none())
+      .then(|| "Not a valid SRM, showing the raw bytes".to_string());
+    Self {
+      path,
+      data,
+      error,
+      is_srm,
+      message,
+      ..Default::default()
+    }
+  }
+
+  fn go_to(&mut self, offset: usize, highlight: Range<usize>) {
+    self.scroll_to = Some(offset / BYTES_PER_ROW);
+    self.highlight = Some(highlight);
+  }
+
+  fn run_search(&mut self) {
+    let needle = if self.search_hex {
+      parse_hex_bytes(&self.search)
+    } else {
+      Some(self.search.as_bytes().to_vec())
+    };
+    let Some(needle) = needle.filter(|n| !n.is_empty()) else {
+      self.matches.clear();
+      self.message = Some("Invalid search value".into());
+      return;
+    };
+
+    self.matches = find_all(&self.data, &needle);
+    self.match_len = needle.len();
+    self.current_match = 0;
+    self.message = Some(match self.matches.len() {
+      0 => "No matches".into(),
+      MAX_MATCHES => format!("{MAX_MATCHES}+ matches"),
+      n => format!("{n} matches"),
+    });
+    self.show_match();
+  }
+
+  fn show_match(&mut self) {
+    if let Some(&offset) = self.matches.get(self.current_match) {
+      self.go_to(offset, offset..offset + self.match_len);
+    }
+  }
+
+  fn show_toolbar(&mut self, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+      ui.label("Offset");
+      let response = ui.add(
+        egui::TextEdit::singleline(&mut self.jump_to)
+          .desired_width(80.0)
+          .hint_text("hex"),
+      );
+      if ui.button("Go").clicked()
+        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+      {
+        match parse_offset(&self.jump_to).filter(|o| *o < self.data.len()) {
+          Some(offset) => {
+            self.go_to(offset, offset..offset + 1);
+            self.message = None;
+          }
+          None => self.message = Some("Offset out of range".into()),
+        }
+      }
+    });
+
+    ui.horizontal(|ui| {
+      ui.label("Search");
+      let response = ui.add(egui::TextEdit::singleline(&mut self.search).desired_width(120.0));
+      ui.checkbox(&mut self.search_hex, "Hex");
+      if ui.button("Find").clicked()
+        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+      {
+        self.run_search();
+      }
+      ui.add_enabled_ui(self.matches.len() > 1, |ui| {
+        if ui.small_button("◀").clicked() {
+          self.current_match = self
+            .current_match
+            .checked_sub(1)
+            .unwrap_or(self.matches.len() - 1);
+          self.show_match();
+        }
+        if ui.small_button("▶").clicked() {
+          self.current_match = (self.current_match + 1) % self.matches.len();
+          self.show_match();
+        }
+      });
+      if let Some(message) = &self.message {
+        ui.label(message);
+      }
+    });
+
+    if self.is_srm {
+      ui.horizontal_wrapped(|ui| {
+        ui.label("Regions");
+        for region in srm::Region::ALL {
+          let selected = self.highlight.as_ref() == Some(&region.range());
+          if ui.selectable_label(selected, region.to_string()).clicked() {
+            self.go_to(region.range().start, region.range());
+          }
+        }
+      });
+    }
+  }
+
+  fn row_job(&self, ui: &egui::Ui, row: usize) -> LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let color = ui.visuals().text_color();
+    let highlight_bg = ui.visuals().selection.bg_fill;
+    let match_bg = ui.visuals().warn_fg_color.linear_multiply(0.5);
+
+    let start = row * BYTES_PER_ROW;
+    let bytes = &self.data[start..(start + BYTES_PER_ROW).min(self.data.len())];
+    let is_match = |offset: usize| {
+      self
+        .matches
+        .get(self.current_match)
+        .is_some_and(|m| (*m..*m + self.match_len).contains(&offset))
+    };
+    let background = |offset: usize| {
+      if is_match(offset) {
+        match_bg
+      } else if self.highlight.as_ref().is_some_and(|h| h.contains(&offset)) {
+        highlight_bg
+      } else {
+        Color32::TRANSPARENT
+      }
+    };
+    let format = |background| TextFormat {
+      font_id: font_id.clone(),
+      color,
+      background,
+      ..Default::default()
+    };
+
+    let mut job = LayoutJob::default();
+    job.append(
+      &format!("{start:08X}  "),
+      0.0,
+      TextFormat {
+        color: ui.visuals().weak_text_color(),
+        ..format(Color32::TRANSPARENT)
+      },
+    );
+    for i in 0..BYTES_PER_ROW {
+      match bytes.get(i) {
+        Some(byte) => job.append(&format!("{byte:02X}"), 0.0, format(background(start + i))),
+        None => job.append("  ", 0.0, format(Color32::TRANSPARENT)),
+      }
+      job.append(" ", 0.0, format(Color32::TRANSPARENT));
+    }
+    job.append(" ", 0.0, format(Color32::TRANSPARENT));
+    for (i, byte) in bytes.iter().enumerate() {
+      let c = if byte.is_ascii_graphic() || *byte == b' ' {
+        *byte as char
+      } else {
+        '.'
+      };
+      job.append(&c.to_string(), 0.0, format(background(start + i)));
+    }
+    job
+  }
+
+  pub fn show(&mut self, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+      ui.small("File");
+      ui.label(self.path.file_name().unwrap_or_default().to_string_lossy());
+      ui.weak(format!("({} bytes)", self.data.len()));
+    });
+
+    if let Some(error) = &self.error {
+      ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+      return;
+    }
+
+    self.show_toolbar(ui);
+    ui.separator();
+
+    let row_height = ui.text_style_height(&TextStyle::Monospace);
+    let rows = self.data.len().div_ceil(BYTES_PER_ROW);
+    let mut scroll_area = egui::ScrollArea::vertical()
+      .auto_shrink([false; 2])
+      .max_height(ui.ctx().screen_rect().height() * 0.5)
+      .id_source("hex_view_rows");
+    if let Some(row) = self.scroll_to.take() {
+      scroll_area =
+        scroll_area.vertical_scroll_offset(row as f32 * (row_height + ui.spacing().item_spacing.y));
+    }
+    scroll_area.show_rows(ui, row_height, rows, |ui, range| {
+      for row in range {
+        let job = self.row_job(ui, row);
+        ui.label(job);
+      }
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{find_all, parse_hex_bytes, parse_offset};
+
+  #[test]
+  fn parse_search_values() {
+    assert_eq!(parse_offset("0x800"), Some(0x800));
+    assert_eq!(parse_offset(" 1f "), Some(0x1f));
+    assert_eq!(parse_offset("xyz"), None);
+
+    assert_eq!(
+      parse_hex_bytes("de ad BE EF"),
+      Some(vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    assert_eq!(parse_hex_bytes("abc"), None);
+    assert_eq!(parse_hex_bytes("+f"), None);
+    assert_eq!(parse_offset("+f"), None);
+    assert_eq!(find_all(b"abcabcab", b"ab"), vec![0, 3, 6]);
+    assert!(find_all(b"ab", b"abc").is_empty());
+  }
+}
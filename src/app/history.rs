@@ -0,0 +1,328 @@
+use super::{data_dir::data_dir, options::Options, overwrite::format_time, work::GenericError};
+
+use crate::widgets::trim_label::UiTrimLabel;
+
+use serde::{Deserialize, Serialize};
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "result", content = "error", rename_all = "lowercase")]
+pub(crate) enum Outcome {
+  Converted,
+  /// The outputs were identical to the existing files
+  Unchanged,
+  /// Every output was skipped, as it already existed
+  Skipped,
+  Failed(String),
+}
+
+impl Outcome {
+  pub fn name(&self) -> &str {
+    match self {
+      Outcome::Converted => "converted",
+      Outcome::Unchanged => "unchanged",
+      Outcome::Skipped => "skipped",
+      Outcome::Failed(_) => "failed",
+    }
+  }
+}
+
+/// What happened to one group in a conversion run
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct GroupRun {
+  pub group: String,
+  pub inputs: Vec<PathBuf>,
+  pub outputs: Vec<PathBuf>,
+  #[serde(flatten)]
+  pub outcome: Outcome,
+}
+
+/// The options a run used
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RunOptions {
+  pub overwrite: bool,
+  pub ask_overwrite: bool,
+  pub swap_bytes: bool,
+  pub auto_byte_order: bool,
+  pub output_mupen: bool,
+  pub output_dir: Option<PathBuf>,
+  pub name_template: String,
+  pub mirror_dirs: bool,
+  pub preserve_times: bool,
+  pub skip_identical: bool,
+}
+
+impl RunOptions {
+  pub fn new(options: &Options) -> Self {
+    Self {
+      overwrite: options.user_params.overwrite,
+      ask_overwrite: options.ask_overwrite,
+      swap_bytes: options.user_params.swap_bytes,
+      auto_byte_order: options.auto_byte_order,
+      output_mupen: options.output_mupen,
+      output_dir: options.output_dir.clone(),
+      name_template: options.name_template.clone(),
+      mirror_dirs: options.mirror_dirs,
+      preserve_times: options.preserve_times,
+      skip_identical: options.skip_identical,
+    }
+  }
+
+  /// Gets the names of the options which are on
+  fn enabled(&self) -> Vec<String> {
+    let Ok(serde_json::Value::Object(options)) = serde_json::to_value(self) else {
+      return Vec::new();
+    };
+    options
+      .into_iter()
+      .filter(|(_, value)| *value == serde_json::Value::Bool(true))
+      .map(|(name, _)| name)
+      .collect()
+  }
+}
+
+impl std::fmt::Display for RunOptions {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let json = serde_json::to_string(self).map_err(|_| std::fmt::Error)?;
+    f.write_str(&json)
+  }
+}
+
+/// Keeps times as whole seconds since the Unix epoch
+mod unix_secs {
+  use serde::{Deserialize, Deserializer, Serializer};
+  use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+  pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    let secs = time
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_secs();
+    serializer.serialize_u64(secs)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+    u64::deserialize(deserializer).map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+  }
+}
+
+/// A conversion run, as kept in the history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Run {
+  #[serde(with = "unix_secs")]
+  pub time: SystemTime,
+  pub options: RunOptions,
+  pub groups: Vec<GroupRun>,
+}
+
+impl Run {
+  pub fn new(options: &Options, groups: Vec<GroupRun>) -> Self {
+    Self {
+      time: SystemTime::now(),
+      options: RunOptions::new(options),
+      groups,
+    }
+  }
+
+  /// Gets the folder the outputs of the run were written to
+  pub fn output_dir(&self) -> Option<PathBuf> {
+    match &self.options.output_dir {
+      Some(dir) => Some(dir.clone()),
+      None => self
+        .groups
+        .iter()
+        .flat_map(|g| &g.outputs)
+        .find_map(|output| output.parent())
+        .map(Path::to_path_buf),
+    }
+  }
+
+  fn count(&self, name: &str) -> usize {
+    self
+      .groups
+      .iter()
+      .filter(|g| g.outcome.name() == name)
+      .count()
+  }
+
+  /// Summarizes the run in a line
+  pub fn summary(&self) -> String {
+    let mut summary = format!("{} converted", self.count("converted"));
+    for name in ["unchanged", "skipped", "failed"] {
+      let count = self.count(name);
+      if count > 0 {
+        summary.push_str(&format!(", {count} {name}"));
+      }
+    }
+    summary
+  }
+}
+
+fn history_path() -> Result<PathBuf, GenericError> {
+  data_dir()
+    .map(|dir| dir.join(HISTORY_FILE))
+    .map_err(|e| GenericError::new(e, HISTORY_FILE.into()))
+}
+
+/// Appends the run to the history file
+pub(crate) fn append(run: &Run) -> Result<(), GenericError> {
+  let path = history_path()?;
+  std::fs::File::options()
+    .create(true)
+    .append(true)
+    .open(&path)
+    .and_then(|mut file| {
+      serde_json::to_writer(&mut file, run)?;
+      writeln!(file)
+    })
+    .map_err(|e| GenericError::new(e, path))
+}
+
+/// Reads every run in the history, skipping lines which can not be read
+fn load() -> Result<Vec<Run>, GenericError> {
+  let path = history_path()?;
+  let text = match std::fs::read_to_string(&path) {
+    Ok(text) => text,
+    Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(e) => return Err(GenericError::new(e, path)),
+  };
+  Ok(
+    text
+      .lines()
+      .filter_map(|line| serde_json::from_str(line).ok())
+      .collect(),
+  )
+}
+
+/// Lists the past conversion runs
+#[derive(Debug)]
+pub(crate) struct History {
+  runs: Result<Vec<Run>, String>,
+  filter: String,
+}
+
+impl History {
+  pub fn load() -> Self {
+    Self {
+      runs: load().map_err(|e| e.to_string()),
+      filter: String::new(),
+    }
+  }
+
+  fn show_run(ui: &mut egui::Ui, i: usize, run: &Run, groups: &[&GroupRun]) {
+    if let Some(dir) = run.output_dir() {
+      if ui.link("Open output folder").clicked() {
+        open::that_in_background(dir);
+      }
+    }
+    let enabled = run.options.enabled();
+    if !enabled.is_empty() {
+      ui.weak(format!("Options: {}", enabled.join(", ")));
+    }
+
+    egui::Grid::new(("history_groups", i))
+      .num_columns(3)
+      .striped(true)
+      .show(ui, |ui| {
+        for group in groups {
+          ui.label(&group.group);
+          match &group.outcome {
+            Outcome::Failed(error) => {
+              ui.colored_label(ui.visuals().error_fg_color, "failed")
+                .on_hover_text(error);
+            }
+            outcome => {
+              ui.label(outcome.name());
+            }
+          }
+          let outputs = group
+            .outputs
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>();
+          let inputs = group
+            .inputs
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>();
+          ui.trim_label(outputs.join(", "), false)
+            .on_hover_text(format!("From: {}", inputs.join(", ")));
+          ui.end_row();
+        }
+      });
+  }
+
+  pub fn show(&mut self, ui: &mut egui::Ui) {
+    let runs = match &self.runs {
+      Ok(runs) => runs,
+      Err(error) => {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+        return;
+      }
+    };
+    if runs.is_empty() {
+      ui.label("No conversion was recorded yet.");
+      return;
+    }
+
+    ui.horizontal(|ui| {
+      ui.label("Group");
+      ui.text_edit_singleline(&mut self.filter);
+    });
+    ui.add_space(3.0);
+
+    let filter = self.filter.to_lowercase();
+    for (i, run) in runs.iter().enumerate().rev() {
+      let groups = run
+        .groups
+        .iter()
+        .filter(|g| g.group.to_lowercase().contains(&filter))
+        .collect::<Vec<_>>();
+      if groups.is_empty() {
+        continue;
+      }
+      egui::CollapsingHeader::new(format!("{} — {}", format_time(run.time), run.summary()))
+        .id_source(("history_run", i))
+        .show(ui, |ui| Self::show_run(ui, i, run, &groups));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::{Duration, UNIX_EPOCH};
+
+  #[test]
+  fn runs_round_trip() {
+    let run = Run {
+      time: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+      options: RunOptions::new(&Options::default()),
+      groups: vec![
+        GroupRun {
+          group: "Zelda".into(),
+          inputs: vec!["in/Zelda.srm".into()],
+          outputs: vec!["out/Zelda.eep".into(), "out/Zelda.mpk1".into()],
+          outcome: Outcome::Converted,
+        },
+        GroupRun {
+          group: "Mario".into(),
+          inputs: vec!["in/Mario.eep".into()],
+          outputs: Vec::new(),
+          outcome: Outcome::Failed("bad \"file\"".into()),
+        },
+      ],
+    };
+    let line = serde_json::to_string(&run).unwrap();
+    assert!(line.starts_with("{\"time\":1700000000,"));
+    assert!(line.contains("\"result\":\"failed\",\"error\":\"bad \\\"file\\\"\""));
+    assert_eq!(serde_json::from_str::<Run>(&line).unwrap(), run);
+    assert_eq!(run.output_dir(), Some("out".into()));
+    assert_eq!(run.summary(), "1 converted, 1 failed");
+  }
+}
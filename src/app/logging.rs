@@ -0,0 +1,259 @@
+use super::{data_dir::data_dir, overwrite::format_time};
+
+use serde::Serialize;
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE: &str = "ramp64-convert-gui.log";
+
+/// Size a log file reaches before it is rotated
+const MAX_SIZE: u64 = 1024 * 1024;
+
+/// Rotated log files kept, besides the current one
+const MAX_ROTATED: usize = 4;
+
+/// Most records kept for the log window
+const MAX_RECENT: usize = 2000;
+
+/// The module path of this application, to tell its records apart from the libraries
+const TARGET: &str = env!("CARGO_CRATE_NAME");
+
+static RECENT: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone, Debug)]
+struct Entry {
+  time: SystemTime,
+  level: log::Level,
+  target: String,
+  message: String,
+}
+
+impl Entry {
+  /// Writes the record as a JSON line
+  fn to_json(&self) -> String {
+    #[derive(Serialize)]
+    struct Line<'a> {
+      time: String,
+      level: &'a str,
+      target: &'a str,
+      message: &'a str,
+    }
+
+    let line = Line {
+      time: format_time(self.time),
+      level: self.level.as_str(),
+      target: &self.target,
+      message: &self.message,
+    };
+    serde_json::to_string(&line).unwrap_or_default() + "\n"
+  }
+}
+
+/// The current log file, rotated once it grows too big
+struct LogFile {
+  dir: PathBuf,
+  file: std::fs::File,
+  size: u64,
+}
+
+impl LogFile {
+  fn open(dir: PathBuf) -> std::io::Result<Self> {
+    std::fs::create_dir_all(&dir)?;
+    let file = std::fs::File::options()
+      .create(true)
+      .append(true)
+      .open(dir.join(LOG_FILE))?;
+    let size = file.metadata()?.len();
+    Ok(Self { dir, file, size })
+  }
+
+  fn write(&mut self, line: &str) -> std::io::Result<()> {
+    if self.size > 0 && self.size + line.len() as u64 > MAX_SIZE {
+      rotate(&self.dir)?;
+      *self = Self::open(self.dir.clone())?;
+    }
+    self.file.write_all(line.as_bytes())?;
+    self.size += line.len() as u64;
+    Ok(())
+  }
+}
+
+/// Renames the log files so the current one becomes `.1`, dropping the oldest
+fn rotate(dir: &Path) -> std::io::Result<()> {
+  let rotated = |i: usize| dir.join(format!("{LOG_FILE}.{i}"));
+  match std::fs::remove_file(rotated(MAX_ROTATED)) {
+    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+    _ => {}
+  }
+  for i in (1..MAX_ROTATED).rev() {
+    if rotated(i).exists() {
+      std::fs::rename(rotated(i), rotated(i + 1))?;
+    }
+  }
+  std::fs::rename(dir.join(LOG_FILE), rotated(1))
+}
+
+struct Logger {
+  file: Mutex<Option<LogFile>>,
+}
+
+impl log::Log for Logger {
+  fn enabled(&self, metadata: &log::Metadata) -> bool {
+    // the libraries are only heard from when something goes wrong
+    metadata.level() <= log::Level::Warn || metadata.target().starts_with(TARGET)
+  }
+
+  fn log(&self, record: &log::Record) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+    let entry = Entry {
+      time: SystemTime::now(),
+      level: record.level(),
+      target: record.target().to_string(),
+      message: record.args().to_string(),
+    };
+    if let Ok(mut file) = self.file.lock() {
+      if let Some(log_file) = file.as_mut() {
+        // without a log file there is nowhere to report its errors
+        if log_file.write(&entry.to_json()).is_err() {
+          *file = None;
+        }
+      }
+    }
+    if let Ok(mut recent) = RECENT.lock() {
+      if recent.len() == MAX_RECENT {
+        recent.pop_front();
+      }
+      recent.push_back(entry);
+    }
+  }
+
+  fn flush(&self) {
+    if let Ok(mut file) = self.file.lock() {
+      if let Some(log_file) = file.as_mut() {
+        let _ = log_file.file.flush();
+      }
+    }
+  }
+}
+
+/// Gets the directory the log files are written to
+fn log_dir() -> std::io::Result<PathBuf> {
+  data_dir().map(|dir| dir.join(LOG_DIR))
+}
+
+/// Starts logging to the log files, keeping the records in memory if they can not be opened
+pub(crate) fn init() {
+  let file = log_dir().and_then(LogFile::open);
+  let error = file.as_ref().err().map(ToString::to_string);
+  let logger = Logger {
+    file: Mutex::new(file.ok()),
+  };
+  if log::set_boxed_logger(Box::new(logger)).is_ok() {
+    log::set_max_level(log::LevelFilter::Debug);
+  }
+  match error {
+    Some(error) => log::warn!("Could not open the log file: {error}"),
+    None => log::info!("Started {} {}", TARGET, env!("CARGO_PKG_VERSION")),
+  }
+}
+
+/// Lists the recent log records
+#[derive(Debug)]
+pub(crate) struct LogView {
+  level: log::Level,
+}
+
+impl Default for LogView {
+  fn default() -> Self {
+    Self {
+      level: log::Level::Info,
+    }
+  }
+}
+
+impl LogView {
+  pub fn show(&mut self, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+      ui.label("Level");
+      for level in [
+        log::Level::Error,
+        log::Level::Warn,
+        log::Level::Info,
+        log::Level::Debug,
+      ] {
+        ui.selectable_value(&mut self.level, level, level.as_str());
+      }
+      if let Ok(dir) = log_dir() {
+        ui.separator();
+        if ui.link("Open log folder").clicked() {
+          open::that_in_background(dir);
+        }
+      }
+    });
+    ui.add_space(3.0);
+
+    let Ok(recent) = RECENT.lock() else {
+      return;
+    };
+    let entries = recent
+      .iter()
+      .filter(|entry| entry.level <= self.level)
+      .collect::<Vec<_>>();
+    if entries.is_empty() {
+      ui.label("Nothing was logged at this level yet.");
+      return;
+    }
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+    egui::ScrollArea::both()
+      .auto_shrink([false; 2])
+      .stick_to_bottom(true)
+      .show_rows(ui, row_height, entries.len(), |ui, rows| {
+        for entry in &entries[rows] {
+          ui.horizontal(|ui| {
+            ui.weak(format_time(entry.time));
+            match entry.level {
+              log::Level::Error => ui.colored_label(ui.visuals().error_fg_color, "ERROR"),
+              log::Level::Warn => ui.colored_label(ui.visuals().warn_fg_color, "WARN"),
+              level => ui.label(level.as_str()),
+            }
+            .on_hover_text(&entry.target);
+            ui.add(egui::Label::new(&entry.message).wrap(false));
+          });
+        }
+      });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::test_dir::TestDir;
+
+  #[test]
+  fn rotate_files() {
+    let dir = TestDir::new("logging");
+    let mut log_file = LogFile::open(dir.to_path_buf()).unwrap();
+    let line = format!("{}\n", "x".repeat(1000));
+    for _ in 0..(MAX_ROTATED + 2) * (MAX_SIZE as usize / line.len() + 1) {
+      log_file.write(&line).unwrap();
+    }
+    drop(log_file);
+
+    let mut names = std::fs::read_dir(&dir)
+      .unwrap()
+      .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+      .collect::<Vec<_>>();
+    names.sort();
+    let mut expected = vec![LOG_FILE.to_string()];
+    expected.extend((1..=MAX_ROTATED).map(|i| format!("{LOG_FILE}.{i}")));
+    assert_eq!(names, expected);
+    assert!(std::fs::metadata(dir.join(LOG_FILE)).unwrap().len() <= MAX_SIZE);
+  }
+}
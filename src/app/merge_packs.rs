@@ -0,0 +1,263 @@
+use super::{
+  controller_pack::{self, InsertError, DATA_PAGES, PACK_SIZE},
+  work::GenericError,
+};
+
+use crate::widgets::{
+  browser::{Browse, FileFilter, UiBrowser},
+  trim_label::UiTrimLabel,
+};
+
+const SOURCE_FILTERS: [FileFilter; 1] = [FileFilter::new(
+  "Controller Packs",
+  &["mpk", "mpk1", "mpk2", "mpk3", "mpk4", "srm"],
+)];
+const PLAYER_PACK_FILTERS: [FileFilter; 1] = [FileFilter::new("Player 1 Pack", &["mpk1"])];
+const MUPEN_PACK_FILTERS: [FileFilter; 1] = [FileFilter::new("Mupen Pack", &["mpk"])];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NoteStatus {
+  Merged,
+  Collision,
+  Rejected(InsertError),
+}
+
+impl std::fmt::Display for NoteStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      NoteStatus::Merged => f.write_str("Merged"),
+      NoteStatus::Collision => f.write_str("Skipped: same name as a merged note"),
+      NoteStatus::Rejected(error) => f.write_fmt(format_args!("Skipped: {error}")),
+    }
+  }
+}
+
+#[derive(Debug)]
+struct MergedNote {
+  source: String,
+  name: String,
+  pages: usize,
+  status: NoteStatus,
+}
+
+#[derive(Debug)]
+struct Merge {
+  pack: Vec<u8>,
+  notes: Vec<MergedNote>,
+}
+
+/// Merges the notes of every pack in the given files, in order, into a new pack
+fn merge(sources: &[std::path::PathBuf]) -> Result<Merge, GenericError> {
+  let mut pack = vec![0u8; PACK_SIZE];
+  controller_pack::format(&mut pack);
+
+  let mut merged: Vec<controller_pack::Note> = Vec::new();
+  let mut notes = Vec::new();
+  for path in sources {
+    let data = std::fs::read(path).map_err(|e| GenericError::new(e, path.clone()))?;
+    let Some(offsets) = controller_pack::pack_offsets(data.len()) else {
+      return Err(GenericError::new(
+        std::io::Error::new(
+          std::io::ErrorKind::InvalidData,
+          "not a controller pack or uncompressed SRM",
+        ),
+        path.clone(),
+      ));
+    };
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    for (i, offset) in offsets.iter().enumerate() {
+      let source = if offsets.len() > 1 {
+        format!("{file_name} (Pack {})", i + 1)
+      } else {
+        file_name.to_string()
+      };
+
+      for note in controller_pack::read_notes(&data[*offset..offset + PACK_SIZE]) {
+        let status = if merged.iter().any(|m| m.collides_with(&note)) {
+          NoteStatus::Collision
+        } else {
+          match controller_pack::insert_note(&mut pack, &note) {
+            Ok(()) => NoteStatus::Merged,
+            Err(error) => NoteStatus::Rejected(error),
+          }
+        };
+        notes.push(MergedNote {
+          source: source.clone(),
+          name: note.name(),
+          pages: note.pages(),
+          status,
+        });
+        if status == NoteStatus::Merged {
+          merged.push(note);
+        }
+      }
+    }
+  }
+
+  Ok(Merge { pack, notes })
+}
+
+#[derive(Debug)]
+pub(crate) struct MergePacks {
+  sources: Vec<std::path::PathBuf>,
+  target: Option<std::path::PathBuf>,
+  output_mupen: bool,
+  add_to_entries: bool,
+  preview: Option<Result<Merge, GenericError>>,
+  message: Option<String>,
+}
+
+impl Default for MergePacks {
+  fn default() -> Self {
+    Self {
+      sources: Vec::new(),
+      target: None,
+      output_mupen: false,
+      add_to_entries: true,
+      preview: None,
+      message: None,
+    }
+  }
+}
+
+impl MergePacks {
+  fn write(&self, merge: &Merge, target: &std::path::Path) -> std::io::Result<()> {
+    let mut data = merge.pack.clone();
+    if self.output_mupen {
+      let mut empty = vec![0u8; PACK_SIZE];
+      controller_pack::format(&mut empty);
+      for _ in 1..4 {
+        data.extend_from_slice(&empty);
+      }
+    }
+    std::fs::write(target, data)
+  }
+
+  /// Shows the merge tool, returning the path of the written pack if it should be added to the entries
+  pub fn show(&mut self, ui: &mut egui::Ui) -> Option<std::path::PathBuf> {
+    let mut changed = false;
+
+    ui.heading("Source Packs");
+    let mut remove = None;
+    for (i, source) in self.sources.iter().enumerate() {
+      ui.horizontal(|ui| {
+        if ui.small_button("✖").on_hover_text("Remove").clicked() {
+          remove = Some(i);
+        }
+        ui.trim_label(source.to_string_lossy(), false);
+      });
+    }
+    if let Some(i) = remove {
+      self.sources.remove(i);
+      changed = true;
+    }
+
+    let mut new_source = None;
+    if ui
+      .browse(
+        &mut new_source,
+        Browse::pick_file(&SOURCE_FILTERS).set_default_text("Add Pack..."),
+      )
+      .changed()
+    {
+      self.sources.extend(new_source);
+      changed = true;
+    }
+
+    if changed || (self.preview.is_none() && !self.sources.is_empty()) {
+      self.preview = (!self.sources.is_empty()).then(|| merge(&self.sources));
+      self.message = None;
+    }
+
+    ui.add_space(6.0);
+    let mut written = None;
+    let preview = self.preview.take();
+    match &preview {
+      None => {
+        ui.label("Add the packs whose notes should be merged.");
+      }
+      Some(Err(error)) => {
+        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+      }
+      Some(Ok(merge)) => {
+        ui.heading("Notes");
+        egui::Grid::new("merge_notes")
+          .num_columns(4)
+          .striped(true)
+          .show(ui, |ui| {
+            ui.strong("Source");
+            ui.strong("Note");
+            ui.strong("Pages");
+            ui.strong("Status");
+            ui.end_row();
+            for note in &merge.notes {
+              ui.label(&note.source);
+              ui.label(&note.name);
+              ui.label(note.pages.to_string());
+              if note.status == NoteStatus::Merged {
+                ui.label(note.status.to_string());
+              } else {
+                ui.colored_label(ui.visuals().warn_fg_color, note.status.to_string());
+              }
+              ui.end_row();
+            }
+          });
+        ui.label(format!(
+          "Free pages after merge: {} of {DATA_PAGES}",
+          controller_pack::free_pages(&merge.pack)
+        ));
+
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+          ui.label("Output");
+          if ui
+            .radio_value(&mut self.output_mupen, false, "Player 1 Pack")
+            .changed()
+            | ui
+              .radio_value(&mut self.output_mupen, true, "Mupen Pack")
+              .changed()
+          {
+            self.target = None;
+          }
+        });
+        ui.horizontal(|ui| {
+          ui.label("Target");
+          ui.centered_and_justified(|ui| {
+            let (name, filters) = if self.output_mupen {
+              ("merged.mpk", &MUPEN_PACK_FILTERS)
+            } else {
+              ("merged.mpk1", &PLAYER_PACK_FILTERS)
+            };
+            ui.browse(&mut self.target, Browse::save_file(Some(name), filters));
+          });
+        });
+        ui.checkbox(&mut self.add_to_entries, "Add Merged Pack to Entries");
+
+        ui.add_enabled_ui(self.target.is_some(), |ui| {
+          if ui.button("Merge").clicked() {
+            let target = self.target.as_ref().unwrap();
+            match self.write(merge, target) {
+              Ok(()) => {
+                self.message = Some(format!("Merged pack written to {}", target.display()));
+                if self.add_to_entries {
+                  written = Some(target.clone());
+                }
+              }
+              Err(error) => {
+                self.message = Some(GenericError::new(error, target.clone()).to_string());
+              }
+            }
+          }
+        });
+      }
+    }
+
+    self.preview = preview;
+
+    if let Some(message) = &self.message {
+      ui.label(message);
+    }
+    written
+  }
+}
@@ -1,19 +1,88 @@
+use super::output;
+
 use ramp64_srm_convert_lib::UserParams;
 
-#[derive(Debug, Default)]
+/// Group used to show an example of the naming template
+const EXAMPLE_GROUP: &str = "Super Mario 64 (USA)";
+
+#[derive(Clone, Debug, Default)]
 pub(crate) struct Options {
   pub user_params: UserParams,
   pub output_mupen: bool,
+  /// Ask before overwriting each existing file
+  pub ask_overwrite: bool,
+  /// Use the detected byte order of each EEP/FLA instead of swap bytes
+  pub auto_byte_order: bool,
   pub output_dir: Option<std::path::PathBuf>,
+  /// Names the output files, relative to the output directory
+  pub name_template: String,
+  pub recursive_scan: bool,
+  /// Recreate the folders of scanned files, relative to the scanned directory, in the output directory
+  pub mirror_dirs: bool,
+  /// Give each output the modification time of its newest input
+  pub preserve_times: bool,
+  /// Leave existing outputs alone when their contents would not change
+  pub skip_identical: bool,
+  /// Leave existing outputs alone when they are newer than the inputs, as in watch mode
+  pub skip_newer: bool,
 }
 
 impl Options {
   pub fn show(&mut self, ui: &mut egui::Ui) {
-    ui.checkbox(&mut self.user_params.overwrite, "Overwrite Existing Files");
+    ui.horizontal(|ui| {
+      ui.label("Existing Files");
+      let mut mode = (self.user_params.overwrite, self.ask_overwrite);
+      ui.radio_value(&mut mode, (false, false), "Keep");
+      ui.radio_value(&mut mode, (true, false), "Overwrite");
+      ui.radio_value(&mut mode, (false, true), "Ask");
+      (self.user_params.overwrite, self.ask_overwrite) = mode;
+    });
     ui.checkbox(
       &mut self.user_params.swap_bytes,
       "Swap Bytes (EEP/FlashRAM)",
     );
+    ui.checkbox(
+      &mut self.auto_byte_order,
+      "Use Detected Byte Order per File",
+    )
+    .on_hover_text("Files with an unknown byte order still use Swap Bytes");
+    ui.checkbox(
+      &mut self.skip_identical,
+      "Skip Outputs Identical to Existing Files",
+    )
+    .on_hover_text("Existing files are compared by hash, and left untouched when equal");
     ui.checkbox(&mut self.output_mupen, "Output Mupen Pack on Split");
+    ui.checkbox(&mut self.preserve_times, "Keep Modification Time of Inputs")
+      .on_hover_text("Outputs get the time of the SRM on split, or of the newest save on create");
+    ui.checkbox(
+      &mut self.recursive_scan,
+      "Include Subdirectories When Adding a Folder",
+    );
+    ui.add_enabled(
+      self.output_dir.is_some(),
+      egui::Checkbox::new(
+        &mut self.mirror_dirs,
+        "Mirror Input Folders in Output Directory",
+      ),
+    )
+    .on_disabled_hover_text("Select an output directory first");
+
+    ui.add_space(6.0);
+    ui.label("Output Name Template");
+    ui.add(egui::TextEdit::singleline(&mut self.name_template).hint_text(output::DEFAULT_TEMPLATE))
+      .on_hover_text(format!("Placeholders: {}", output::placeholders()));
+    match output::validate(&self.name_template) {
+      Ok(()) => {
+        let examples = ["srm", "eep", "mpk1"]
+          .map(|kind| output::render(&self.name_template, EXAMPLE_GROUP, kind).unwrap_or_default());
+        ui.weak(format!(
+          "Example: {}",
+          examples.map(|e| e.display().to_string()).join(", ")
+        ));
+      }
+      Err(error) => {
+        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+      }
+    }
   }
 }
@@ -0,0 +1,583 @@
+use super::work::GenericError;
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+pub(crate) const DEFAULT_TEMPLATE: &str = "{group}";
+
+/// Placeholders accepted by the naming template
+const PLACEHOLDERS: [&str; 4] = ["group", "type", "rom_name", "player"];
+
+/// Extensions of the save types, replaced by the output type when the template ends with one
+const SAVE_EXTENSIONS: [&str; 9] = [
+  "srm", "eep", "sra", "fla", "mpk", "mpk1", "mpk2", "mpk3", "mpk4",
+];
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum TemplateError {
+  Unclosed,
+  Unknown(String),
+  Empty,
+  /// The template points outside of the output directory
+  NotRelative,
+}
+
+impl std::fmt::Display for TemplateError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TemplateError::Unclosed => f.write_str("a placeholder is missing its closing brace"),
+      TemplateError::Unknown(name) => f.write_fmt(format_args!("unknown placeholder {{{name}}}")),
+      TemplateError::Empty => f.write_str("the template produces an empty file name"),
+      TemplateError::NotRelative => {
+        f.write_str("the template must stay inside the output directory")
+      }
+    }
+  }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Gets the game name of the group, without tags like `(USA)` or `[!]`
+fn rom_name(group: &str) -> String {
+  let mut name = String::with_capacity(group.len());
+  let mut depth = 0usize;
+  for c in group.chars() {
+    match c {
+      '(' | '[' => depth += 1,
+      ')' | ']' => depth = depth.saturating_sub(1),
+      c if depth == 0 => name.push(c),
+      _ => {}
+    }
+  }
+  let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+  if name.is_empty() {
+    group.to_string()
+  } else {
+    name
+  }
+}
+
+/// Gets the player of a controller pack file type
+fn player(kind: &str) -> &str {
+  kind.strip_prefix("mpk").unwrap_or_default()
+}
+
+/// Renders the output path, relative to the output directory, of the given group file type
+pub(crate) fn render(template: &str, group: &str, kind: &str) -> Result<PathBuf, TemplateError> {
+  let template = if template.trim().is_empty() {
+    DEFAULT_TEMPLATE
+  } else {
+    template.trim()
+  };
+
+  let mut rendered = String::with_capacity(template.len() + group.len());
+  let mut rest = template;
+  while let Some(start) = rest.find('{') {
+    rendered.push_str(&rest[..start]);
+    let Some(end) = rest[start..].find('}') else {
+      return Err(TemplateError::Unclosed);
+    };
+    let name = &rest[start + 1..start + end];
+    match name {
+      "group" => rendered.push_str(group),
+      "type" => rendered.push_str(kind),
+      "rom_name" => rendered.push_str(&rom_name(group)),
+      "player" => rendered.push_str(player(kind)),
+      _ => return Err(TemplateError::Unknown(name.to_string())),
+    }
+    rest = &rest[start + end + 1..];
+  }
+  rendered.push_str(rest);
+
+  if rendered
+    .rsplit(['/', '\\'])
+    .next()
+    .unwrap_or_default()
+    .trim()
+    .is_empty()
+  {
+    return Err(TemplateError::Empty);
+  }
+
+  let name_start = rendered.rfind(['/', '\\']).map_or(0, |i| i + 1);
+  if let Some(dot) = rendered[name_start..].rfind('.') {
+    let extension = rendered[name_start + dot + 1..].to_ascii_lowercase();
+    if SAVE_EXTENSIONS.contains(&extension.as_str()) {
+      rendered.truncate(name_start + dot);
+    }
+  }
+  rendered.push('.');
+  rendered.push_str(kind);
+
+  let path = PathBuf::from(rendered);
+  if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+    return Err(TemplateError::NotRelative);
+  }
+  Ok(path)
+}
+
+/// Checks the template, returning the first problem found
+pub(crate) fn validate(template: &str) -> Result<(), TemplateError> {
+  render(template, "group", "eep").map(|_| ())
+}
+
+/// Lists the placeholders for display
+pub(crate) fn placeholders() -> String {
+  PLACEHOLDERS.map(|p| format!("{{{p}}}")).join(", ")
+}
+
+/// Where the output files of a group are placed
+#[derive(Debug)]
+pub(crate) struct Destination {
+  /// Name of the file the converter names its outputs after
+  file_name: PathBuf,
+  group: String,
+  /// Output directory, which also holds the staging directory
+  pub out_dir: PathBuf,
+  sub_dir: PathBuf,
+}
+
+impl Destination {
+  pub fn new(main_path: &Path, output_dir: Option<&Path>, sub_dir: PathBuf) -> Self {
+    let out_dir = output_dir
+      .or(main_path.parent())
+      .unwrap_or(Path::new(""))
+      .to_path_buf();
+    Self {
+      file_name: main_path.file_name().unwrap_or_default().into(),
+      group: main_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned(),
+      out_dir,
+      sub_dir,
+    }
+  }
+
+  /// Gets the final path of the given output file type
+  pub fn target(&self, template: &str, kind: &str) -> Result<PathBuf, TemplateError> {
+    Ok(
+      self
+        .out_dir
+        .join(&self.sub_dir)
+        .join(render(template, &self.group, kind)?),
+    )
+  }
+
+  /// Gets the name the converter gives to the given output file type
+  pub fn staged_name(&self, kind: &str) -> PathBuf {
+    self.file_name.with_extension(kind)
+  }
+}
+
+/// Temporary directory next to the outputs, where the converter writes before the files are placed
+#[derive(Debug)]
+pub(crate) struct Staging {
+  dir: PathBuf,
+}
+
+impl Staging {
+  pub fn new(base: &Path) -> std::io::Result<Self> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = base.join(format!(
+      ".ramp64-staging-{}-{}",
+      std::process::id(),
+      COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir)?;
+    Ok(Self { dir })
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Gets every file written to the staging directory, with its type
+  pub fn files(&self) -> Result<Vec<(PathBuf, String)>, GenericError> {
+    let entries =
+      std::fs::read_dir(&self.dir).map_err(|e| GenericError::new(e, self.dir.clone()))?;
+    let mut files = entries
+      .flatten()
+      .map(|e| e.path())
+      .filter_map(|path| {
+        let kind = path.extension()?.to_str()?.to_ascii_lowercase();
+        Some((path, kind))
+      })
+      .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+  }
+}
+
+impl Drop for Staging {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.dir);
+  }
+}
+
+/// How an output file is placed when its target already exists
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Decision {
+  Overwrite,
+  Skip,
+  KeepBoth,
+}
+
+#[derive(Debug)]
+pub(crate) struct Placement {
+  pub staged: PathBuf,
+  pub target: PathBuf,
+  /// `None` until the user decides what to do with the existing target
+  pub decision: Option<Decision>,
+  /// The existing target already has the same contents
+  pub unchanged: bool,
+}
+
+/// Output files of a group, converted and waiting to be placed
+#[derive(Debug)]
+pub(crate) struct Staged {
+  staging: Staging,
+  pub placements: Vec<Placement>,
+  /// Modification time given to the placed files, instead of the time they were written
+  modified: Option<SystemTime>,
+}
+
+impl Staged {
+  pub fn new(staging: Staging, placements: Vec<Placement>, modified: Option<SystemTime>) -> Self {
+    Self {
+      staging,
+      placements,
+      modified,
+    }
+  }
+
+  /// Places every output file as decided, then removes the staging directory.
+  ///
+  /// Every file is first written and synced to a temporary file next to its target, and only
+  /// renamed into place once all of them were written. Returns the paths of the placed files.
+  pub fn place(self) -> Result<Vec<PathBuf>, PlaceError> {
+    let mut written: Vec<(TempFile, PathBuf, bool)> = Vec::with_capacity(self.placements.len());
+    let mut result = Ok(());
+    for placement in &self.placements {
+      let (target, overwrite) = match placement.decision {
+        Some(Decision::Overwrite) => (placement.target.clone(), true),
+        Some(Decision::KeepBoth) => (keep_both_path(&placement.target), false),
+        Some(Decision::Skip) | None => continue,
+      };
+      match TempFile::write(&placement.staged, &target, self.modified) {
+        Ok(temp) => written.push((temp, target, overwrite)),
+        Err(error) => {
+          result = Err(error);
+          break;
+        }
+      }
+    }
+
+    let mut leftovers = Vec::new();
+    let mut placed = Vec::with_capacity(written.len());
+    for (temp, target, overwrite) in written {
+      if result.is_ok() {
+        match temp.persist(&target, overwrite) {
+          Ok(()) => placed.push(target),
+          Err((error, temp)) => {
+            result = Err(error);
+            leftovers.extend(temp.remove().err());
+          }
+        }
+      } else {
+        leftovers.extend(temp.remove().err());
+      }
+    }
+    let staging = self.staging.path().to_path_buf();
+    if let Err(error) = std::fs::remove_dir_all(&staging) {
+      leftovers.push(TempFileError {
+        path: staging,
+        error,
+      });
+    }
+
+    match result {
+      Ok(()) if leftovers.is_empty() => Ok(placed),
+      Ok(()) => Err(PlaceError {
+        error: None,
+        placed,
+        leftovers,
+      }),
+      Err(error) => Err(PlaceError {
+        error: Some(error),
+        placed,
+        leftovers,
+      }),
+    }
+  }
+}
+
+/// Placing the files of a group failed, or left temporary files behind
+#[derive(Debug)]
+pub(crate) struct PlaceError {
+  /// Why the files were not placed, if they were not
+  pub error: Option<GenericError>,
+  /// Files placed before the error
+  pub placed: Vec<PathBuf>,
+  pub leftovers: Vec<TempFileError>,
+}
+
+/// A temporary file or directory which could not be removed
+#[derive(Debug)]
+pub(crate) struct TempFileError {
+  pub path: PathBuf,
+  pub error: std::io::Error,
+}
+
+impl std::fmt::Display for TempFileError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_fmt(format_args!(
+      "Could not remove temporary {}: {}",
+      self.path.display(),
+      self.error
+    ))
+  }
+}
+
+impl std::error::Error for TempFileError {}
+
+/// Output file written next to its target, until it is renamed into place
+#[derive(Debug)]
+struct TempFile {
+  path: PathBuf,
+}
+
+impl TempFile {
+  /// Copies the staged file next to the target, and syncs it to the disk
+  fn write(
+    staged: &Path,
+    target: &Path,
+    modified: Option<SystemTime>,
+  ) -> Result<Self, GenericError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let parent = target.parent().unwrap_or(Path::new(""));
+    std::fs::create_dir_all(parent).map_err(|e| GenericError::new(e, parent.to_path_buf()))?;
+    let temp = Self {
+      path: parent.join(format!(
+        ".{}.ramp64-tmp-{}-{}",
+        target.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+      )),
+    };
+
+    let written = std::fs::copy(staged, &temp.path).and_then(|_| {
+      let file = std::fs::File::options().write(true).open(&temp.path)?;
+      if let Some(modified) = modified {
+        file.set_modified(modified)?;
+      }
+      file.sync_all()
+    });
+    match written {
+      Ok(()) => Ok(temp),
+      Err(error) => {
+        let _ = temp.remove();
+        Err(GenericError::new(error, target.to_path_buf()))
+      }
+    }
+  }
+
+  /// Renames the file to the target, giving itself back if it could not
+  fn persist(self, target: &Path, overwrite: bool) -> Result<(), (GenericError, Self)> {
+    if !overwrite && target.exists() {
+      return Err((
+        GenericError::new(
+          std::io::ErrorKind::AlreadyExists.into(),
+          target.to_path_buf(),
+        ),
+        self,
+      ));
+    }
+    if let Err(error) = std::fs::rename(&self.path, target) {
+      return Err((GenericError::new(error, target.to_path_buf()), self));
+    }
+    // makes the rename itself durable
+    #[cfg(unix)]
+    if let Some(parent) = target.parent() {
+      let _ = std::fs::File::open(parent).and_then(|dir| dir.sync_all());
+    }
+    Ok(())
+  }
+
+  fn remove(self) -> Result<(), TempFileError> {
+    std::fs::remove_file(&self.path).map_err(|error| TempFileError {
+      path: self.path,
+      error,
+    })
+  }
+}
+
+/// Gets a free path next to the target, adding a number to its name
+pub(crate) fn keep_both_path(target: &Path) -> PathBuf {
+  let stem = target.file_stem().unwrap_or_default().to_string_lossy();
+  let extension = target
+    .extension()
+    .map(|e| format!(".{}", e.to_string_lossy()))
+    .unwrap_or_default();
+  (1..)
+    .map(|n| target.with_file_name(format!("{stem} ({n}){extension}")))
+    .find(|path| !path.exists())
+    .unwrap()
+}
+
+/// Hashes the contents of a file, along with its length
+fn hash_file(path: &Path) -> std::io::Result<u64> {
+  use std::hash::Hasher;
+  use std::io::Read;
+
+  let mut file = std::fs::File::open(path)?;
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  let mut buf = [0; 8192];
+  let mut len = 0;
+  loop {
+    let read = file.read(&mut buf)?;
+    if read == 0 {
+      break;
+    }
+    hasher.write(&buf[..read]);
+    len += read;
+  }
+  hasher.write_usize(len);
+  Ok(hasher.finish())
+}
+
+/// Checks if both files have the same contents, comparing their hashes
+pub(crate) fn same_contents(a: &Path, b: &Path) -> Result<bool, GenericError> {
+  let len = |path: &Path| {
+    path
+      .metadata()
+      .map(|m| m.len())
+      .map_err(|e| GenericError::new(e, path.to_path_buf()))
+  };
+  if len(a)? != len(b)? {
+    return Ok(false);
+  }
+  let hash = |path: &Path| hash_file(path).map_err(|e| GenericError::new(e, path.to_path_buf()));
+  Ok(hash(a)? == hash(b)?)
+}
+
+/// Gets the modification time of the newest file
+pub(crate) fn newest_modified<'a, I>(paths: I) -> Option<SystemTime>
+where
+  I: IntoIterator<Item = &'a PathBuf>,
+{
+  paths
+    .into_iter()
+    .filter_map(|path| path.metadata().and_then(|m| m.modified()).ok())
+    .max()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::test_dir::TestDir;
+
+  #[test]
+  fn render_templates() {
+    let group = "Zelda (USA) [!]";
+    assert_eq!(render("", group, "eep"), Ok("Zelda (USA) [!].eep".into()));
+    assert_eq!(
+      render("{group}.{type}", group, "srm"),
+      Ok("Zelda (USA) [!].srm".into())
+    );
+    assert_eq!(
+      render("{type}/{rom_name}", group, "sra"),
+      Ok("sra/Zelda.sra".into())
+    );
+    assert_eq!(
+      render("{rom_name}-P{player}", group, "mpk2"),
+      Ok("Zelda-P2.mpk2".into())
+    );
+    assert_eq!(
+      render("{rom_name}-P{player}", group, "srm"),
+      Ok("Zelda-P.srm".into())
+    );
+    assert_eq!(
+      render("{type}/{group}.eep", "X", "srm"),
+      Ok("srm/X.srm".into())
+    );
+    assert_eq!(
+      render("{type}/{group}.eep", "X", "mpk1"),
+      Ok("mpk1/X.mpk1".into())
+    );
+    assert_eq!(render("{group}.SRA", "X", "sra"), Ok("X.sra".into()));
+    assert_eq!(render("{group}", "v1.0", "eep"), Ok("v1.0.eep".into()));
+  }
+
+  #[test]
+  fn reject_bad_templates() {
+    assert_eq!(render("{group", "a", "eep"), Err(TemplateError::Unclosed));
+    assert_eq!(
+      render("{game}", "a", "eep"),
+      Err(TemplateError::Unknown("game".into()))
+    );
+    assert_eq!(
+      render("../{group}", "a", "eep"),
+      Err(TemplateError::NotRelative)
+    );
+    assert_eq!(
+      render("/{group}", "a", "eep"),
+      Err(TemplateError::NotRelative)
+    );
+    assert_eq!(render("{player}", "a", "eep"), Err(TemplateError::Empty));
+  }
+
+  #[test]
+  fn keep_both_names() {
+    let dir = TestDir::new("keep-both");
+    let target = dir.join("Zelda.eep");
+    assert_eq!(keep_both_path(&target), dir.join("Zelda (1).eep"));
+    std::fs::write(dir.join("Zelda (1).eep"), []).unwrap();
+    assert_eq!(keep_both_path(&target), dir.join("Zelda (2).eep"));
+  }
+
+  #[test]
+  fn place_staged_files() {
+    let dir = TestDir::new("place");
+    let out = dir.join("out");
+    let staging = Staging::new(&dir).unwrap();
+    let (eep, sra) = (staging.path().join("a.eep"), staging.path().join("a.sra"));
+    std::fs::write(&eep, [1]).unwrap();
+    std::fs::write(&sra, [2]).unwrap();
+    let placements = vec![
+      Placement {
+        staged: eep,
+        target: out.join("a.eep"),
+        decision: Some(Decision::Overwrite),
+        unchanged: false,
+      },
+      Placement {
+        staged: sra,
+        target: out.join("a.sra"),
+        decision: Some(Decision::Skip),
+        unchanged: false,
+      },
+    ];
+    let staging_path = staging.path().to_path_buf();
+    assert!(Staged::new(staging, placements, None).place().is_ok());
+    assert_eq!(std::fs::read(out.join("a.eep")).unwrap(), [1]);
+    assert!(!out.join("a.sra").exists());
+    assert!(!staging_path.exists());
+    // no temporary file is left behind
+    assert_eq!(std::fs::read_dir(&out).unwrap().count(), 1);
+  }
+
+  #[test]
+  fn compare_contents() {
+    let dir = TestDir::new("same");
+    let (a, b, c) = (dir.join("a"), dir.join("b"), dir.join("c"));
+    std::fs::write(&a, [1, 2, 3]).unwrap();
+    std::fs::write(&b, [1, 2, 3]).unwrap();
+    std::fs::write(&c, [1, 2, 4]).unwrap();
+    assert!(same_contents(&a, &b).unwrap());
+    assert!(!same_contents(&a, &c).unwrap());
+    assert!(same_contents(&a, &dir.join("missing")).is_err());
+  }
+}
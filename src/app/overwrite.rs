@@ -0,0 +1,146 @@
+use super::output::Decision;
+
+use crate::widgets::trim_label::UiTrimLabel;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats the time as an UTC date and time
+pub(crate) fn format_time(time: SystemTime) -> String {
+  let secs = match time.duration_since(UNIX_EPOCH) {
+    Ok(duration) => duration.as_secs() as i64,
+    Err(error) => -(error.duration().as_secs() as i64),
+  };
+  let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+  // civil date from days since 1970-01-01
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+
+  format!(
+    "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+    secs / 3600,
+    secs / 60 % 60,
+    secs % 60
+  )
+}
+
+#[derive(Debug)]
+struct FileInfo {
+  size: u64,
+  modified: Option<SystemTime>,
+}
+
+impl FileInfo {
+  fn read(path: &std::path::Path) -> Option<Self> {
+    let metadata = path.metadata().ok()?;
+    Some(Self {
+      size: metadata.len(),
+      modified: metadata.modified().ok(),
+    })
+  }
+
+  fn show(info: &Option<Self>, ui: &mut egui::Ui) {
+    match info {
+      Some(info) => {
+        ui.label(format!("{} bytes", info.size));
+        ui.label(info.modified.map_or("Unknown".into(), format_time));
+      }
+      None => {
+        ui.weak("Unavailable");
+        ui.label("");
+      }
+    }
+  }
+}
+
+/// Asks what to do with an output file which already exists
+#[derive(Debug)]
+pub(crate) struct Question {
+  group: String,
+  target: std::path::PathBuf,
+  existing: Option<FileInfo>,
+  new: Option<FileInfo>,
+  apply_to_all: bool,
+}
+
+impl Question {
+  pub fn new(group: String, staged: &std::path::Path, target: &std::path::Path) -> Self {
+    Self {
+      group,
+      target: target.to_path_buf(),
+      existing: FileInfo::read(target),
+      new: FileInfo::read(staged),
+      apply_to_all: false,
+    }
+  }
+
+  /// Shows the question, returning the decision and if it applies to every other existing file
+  pub fn show(&mut self, ui: &mut egui::Ui) -> Option<(Decision, bool)> {
+    ui.horizontal(|ui| {
+      ui.small("Group");
+      ui.trim_label(&self.group, false);
+    });
+    ui.label("This output file already exists:");
+    ui.trim_label(self.target.to_string_lossy(), false);
+    ui.add_space(3.0);
+
+    egui::Grid::new("overwrite_files")
+      .num_columns(3)
+      .striped(true)
+      .show(ui, |ui| {
+        ui.label("");
+        ui.strong("Size");
+        ui.strong("Modified");
+        ui.end_row();
+        ui.label("Existing");
+        FileInfo::show(&self.existing, ui);
+        ui.end_row();
+        ui.label("New");
+        FileInfo::show(&self.new, ui);
+        ui.end_row();
+      });
+
+    ui.add_space(6.0);
+    ui.checkbox(&mut self.apply_to_all, "Apply to All");
+    let mut decision = None;
+    ui.horizontal(|ui| {
+      if ui.button("Overwrite").clicked() {
+        decision = Some(Decision::Overwrite);
+      }
+      if ui.button("Skip").clicked() {
+        decision = Some(Decision::Skip);
+      }
+      if ui
+        .button("Keep Both")
+        .on_hover_text("Write the new file with a number added to its name")
+        .clicked()
+      {
+        decision = Some(Decision::KeepBoth);
+      }
+    });
+    decision.map(|d| (d, self.apply_to_all))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::format_time;
+
+  use std::time::{Duration, UNIX_EPOCH};
+
+  #[test]
+  fn format_times() {
+    assert_eq!(format_time(UNIX_EPOCH), "1970-01-01 00:00:00 UTC");
+    assert_eq!(
+      format_time(UNIX_EPOCH + Duration::from_secs(951_782_400 + 3_723)),
+      "2000-02-29 01:02:03 UTC"
+    );
+  }
+}
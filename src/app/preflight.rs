@@ -0,0 +1,191 @@
+use crate::widgets::trim_label::UiTrimLabel;
+
+use std::path::{Path, PathBuf};
+
+/// Something which would make the conversion fail, found before writing anything
+#[derive(Debug)]
+pub(crate) enum Problem {
+  MissingOutputDir(PathBuf),
+  NotWritable {
+    dir: PathBuf,
+    error: std::io::Error,
+  },
+  NotEnoughSpace {
+    dir: PathBuf,
+    needed: u64,
+    available: u64,
+  },
+  UnreadableInput {
+    group: String,
+    path: PathBuf,
+    error: std::io::Error,
+  },
+}
+
+impl Problem {
+  fn kind(&self) -> &str {
+    match self {
+      Problem::MissingOutputDir(_) => "Missing output folder",
+      Problem::NotWritable { .. } => "Output folder not writable",
+      Problem::NotEnoughSpace { .. } => "Not enough space",
+      Problem::UnreadableInput { .. } => "Unreadable input",
+    }
+  }
+}
+
+impl std::fmt::Display for Problem {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Problem::MissingOutputDir(dir) => f.write_fmt(format_args!("{}", dir.display())),
+      Problem::NotWritable { dir, error } => {
+        f.write_fmt(format_args!("{}: {error}", dir.display()))
+      }
+      Problem::NotEnoughSpace {
+        dir,
+        needed,
+        available,
+      } => f.write_fmt(format_args!(
+        "{}: needs {needed} bytes, {available} bytes free",
+        dir.display()
+      )),
+      Problem::UnreadableInput { group, path, error } => {
+        f.write_fmt(format_args!("{group}: {}: {error}", path.display()))
+      }
+    }
+  }
+}
+
+/// Checks that files can be created in the directory
+pub(crate) fn check_writable(dir: &Path) -> Result<(), Problem> {
+  if !dir.is_dir() {
+    return Err(Problem::MissingOutputDir(dir.to_path_buf()));
+  }
+  let probe = dir.join(format!(".ramp64-probe-{}", std::process::id()));
+  std::fs::File::options()
+    .write(true)
+    .create_new(true)
+    .open(&probe)
+    .and_then(|_| std::fs::remove_file(&probe))
+    .map_err(|error| Problem::NotWritable {
+      dir: dir.to_path_buf(),
+      error,
+    })
+}
+
+/// Checks that the directory has room for the given amount of bytes
+pub(crate) fn check_space(dir: &Path, needed: u64) -> Result<(), Problem> {
+  match available_space(dir) {
+    Some(available) if available < needed => Err(Problem::NotEnoughSpace {
+      dir: dir.to_path_buf(),
+      needed,
+      available,
+    }),
+    _ => Ok(()),
+  }
+}
+
+/// Checks that the input file can still be opened for reading
+pub(crate) fn check_readable(group: &str, path: &Path) -> Result<(), Problem> {
+  std::fs::File::open(path)
+    .map(|_| ())
+    .map_err(|error| Problem::UnreadableInput {
+      group: group.to_string(),
+      path: path.to_path_buf(),
+      error,
+    })
+}
+
+/// Gets the free space available to this user in the file system of the directory
+#[cfg(unix)]
+fn available_space(dir: &Path) -> Option<u64> {
+  use std::os::unix::ffi::OsStrExt;
+
+  let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+  let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+  // SAFETY: the path is a valid C string, and statvfs fills the struct on success
+  if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+    return None;
+  }
+  let stat = unsafe { stat.assume_init() };
+  #[allow(clippy::unnecessary_cast)]
+  Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Gets the free space available to this user in the file system of the directory
+#[cfg(windows)]
+fn available_space(dir: &Path) -> Option<u64> {
+  use std::os::windows::ffi::OsStrExt;
+  use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+  let path = dir
+    .as_os_str()
+    .encode_wide()
+    .chain(std::iter::once(0))
+    .collect::<Vec<_>>();
+  let mut available = 0u64;
+  // SAFETY: the path is null terminated, and the totals which are not needed may be null
+  let ok = unsafe {
+    GetDiskFreeSpaceExW(
+      path.as_ptr(),
+      &mut available,
+      std::ptr::null_mut(),
+      std::ptr::null_mut(),
+    )
+  };
+  (ok != 0).then_some(available)
+}
+
+/// Free space is not checked on this platform
+#[cfg(not(any(unix, windows)))]
+fn available_space(_dir: &Path) -> Option<u64> {
+  None
+}
+
+/// Lists every problem found before converting
+#[derive(Debug)]
+pub(crate) struct Report {
+  problems: Vec<Problem>,
+}
+
+impl Report {
+  pub fn new(problems: Vec<Problem>) -> Self {
+    Self { problems }
+  }
+
+  pub fn show(&self, ui: &mut egui::Ui) {
+    ui.label("Nothing was converted, as the following problems were found:");
+    ui.add_space(3.0);
+    egui::Grid::new("preflight_problems")
+      .num_columns(2)
+      .striped(true)
+      .show(ui, |ui| {
+        for problem in &self.problems {
+          ui.strong(problem.kind());
+          ui.trim_label(problem.to_string(), false);
+          ui.end_row();
+        }
+      });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::test_dir::TestDir;
+
+  #[test]
+  fn check_output_dirs() {
+    let dir = TestDir::new("preflight");
+    assert!(matches!(
+      check_writable(&dir.join("missing")),
+      Err(Problem::MissingOutputDir(_))
+    ));
+    assert!(check_writable(&dir).is_ok());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    assert!(check_space(&dir, 0).is_ok());
+    assert!(matches!(
+      check_readable("group", &dir.join("missing.eep")),
+      Err(Problem::UnreadableInput { .. })
+    ));
+  }
+}
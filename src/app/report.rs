@@ -0,0 +1,279 @@
+use super::{
+  error_list::{Category, ErrorList},
+  history::{GroupRun, Outcome},
+  overwrite::format_time,
+  work::GenericError,
+};
+use crate::widgets::browser::FileFilter;
+
+use serde::Serialize;
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub(crate) const REPORT_FILTERS: [FileFilter; 3] = [
+  FileFilter::new("CSV", &["csv"]),
+  FileFilter::new("JSON", &["json"]),
+  FileFilter::new("Markdown", &["md"]),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Format {
+  Csv,
+  Json,
+  Markdown,
+}
+
+impl Format {
+  /// Picks the format from the extension of the file, if it is a known one
+  pub fn from_path(path: &Path) -> Option<Self> {
+    match path
+      .extension()
+      .map(|e| e.to_string_lossy().to_lowercase())
+      .as_deref()
+    {
+      Some("csv") => Some(Format::Csv),
+      Some("json") => Some(Format::Json),
+      Some("md" | "markdown") => Some(Format::Markdown),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct ErrorEntry {
+  category: String,
+  message: String,
+}
+
+/// What a conversion did, ready to attach to a support request
+#[derive(Clone, Debug)]
+pub(crate) struct Report {
+  time: SystemTime,
+  /// The groups which were converted or skipped, failed groups are in the errors
+  groups: Vec<GroupRun>,
+  errors: Vec<ErrorEntry>,
+}
+
+impl Report {
+  pub fn new<C: Category>(groups: &[GroupRun], errors: &ErrorList<C>) -> Self {
+    let errors = errors
+      .iter()
+      .map(|(category, error)| ErrorEntry {
+        category: category.name().to_string(),
+        message: error.to_string(),
+      })
+      .collect::<Vec<_>>();
+    Self {
+      time: SystemTime::now(),
+      groups: groups
+        .iter()
+        .filter(|g| !matches!(g.outcome, Outcome::Failed(_)))
+        .cloned()
+        .collect(),
+      errors,
+    }
+  }
+
+  fn outputs(group: &GroupRun) -> Vec<String> {
+    group
+      .outputs
+      .iter()
+      .map(|p| p.display().to_string())
+      .collect()
+  }
+
+  fn to_csv(&self) -> String {
+    fn field(text: &str) -> String {
+      if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+      } else {
+        text.to_string()
+      }
+    }
+
+    let mut csv = String::from("status,group,category,details\n");
+    for group in &self.groups {
+      csv.push_str(&format!(
+        "{},{},,{}\n",
+        group.outcome.name(),
+        field(&group.group),
+        field(&Self::outputs(group).join("; "))
+      ));
+    }
+    for error in &self.errors {
+      csv.push_str(&format!(
+        "error,,{},{}\n",
+        field(&error.category),
+        field(&error.message)
+      ));
+    }
+    csv
+  }
+
+  fn to_json(&self) -> String {
+    #[derive(Serialize)]
+    struct Group<'a> {
+      group: &'a str,
+      result: &'a str,
+      outputs: Vec<String>,
+    }
+
+    #[derive(Serialize)]
+    struct Json<'a> {
+      time: String,
+      groups: Vec<Group<'a>>,
+      errors: &'a [ErrorEntry],
+    }
+
+    let report = Json {
+      time: format_time(self.time),
+      groups: self
+        .groups
+        .iter()
+        .map(|group| Group {
+          group: &group.group,
+          result: group.outcome.name(),
+          outputs: Self::outputs(group),
+        })
+        .collect(),
+      errors: &self.errors,
+    };
+    serde_json::to_string(&report).unwrap_or_default() + "\n"
+  }
+
+  fn to_markdown(&self) -> String {
+    fn line(text: &str) -> String {
+      text.replace(['\r', '\n'], " ")
+    }
+
+    let mut md = format!("# Conversion Report\n\n{}\n", format_time(self.time));
+    for (title, names) in [
+      ("Converted", &["converted"][..]),
+      ("Skipped", &["unchanged", "skipped"][..]),
+    ] {
+      let groups = self
+        .groups
+        .iter()
+        .filter(|g| names.contains(&g.outcome.name()))
+        .collect::<Vec<_>>();
+      if groups.is_empty() {
+        continue;
+      }
+      md.push_str(&format!("\n## {title}\n\n"));
+      for group in groups {
+        md.push_str(&format!("- {}", line(&group.group)));
+        if names.len() > 1 {
+          md.push_str(&format!(" ({})", group.outcome.name()));
+        }
+        let outputs = Self::outputs(group);
+        if !outputs.is_empty() {
+          md.push_str(&format!(": `{}`", line(&outputs.join("`, `"))));
+        }
+        md.push('\n');
+      }
+    }
+    if !self.errors.is_empty() {
+      md.push_str("\n## Errors\n");
+      let mut category = None;
+      for error in &self.errors {
+        if category != Some(&error.category) {
+          md.push_str(&format!("\n### {}\n\n", error.category));
+          category = Some(&error.category);
+        }
+        md.push_str(&format!("- {}\n", line(&error.message)));
+      }
+    }
+    md
+  }
+
+  pub fn render(&self, format: Format) -> String {
+    match format {
+      Format::Csv => self.to_csv(),
+      Format::Json => self.to_json(),
+      Format::Markdown => self.to_markdown(),
+    }
+  }
+
+  /// Writes the report in the format of the extension, adding `.csv` when the
+  /// path has no known one, and returns the path that was written
+  pub fn export(&self, path: &Path) -> Result<PathBuf, GenericError> {
+    let (path, format) = match Format::from_path(path) {
+      Some(format) => (path.to_path_buf(), format),
+      None => {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".csv");
+        (PathBuf::from(path), Format::Csv)
+      }
+    };
+    std::fs::write(&path, self.render(format)).map_err(|e| GenericError::new(e, path.clone()))?;
+    Ok(path)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::{error::AppError, test_dir::TestDir};
+
+  #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+  struct Test;
+
+  impl Category for Test {
+    fn name(&self) -> &str {
+      "Test"
+    }
+
+    fn description(&self) -> &str {
+      ""
+    }
+  }
+
+  #[test]
+  fn render_formats() {
+    let group = |name: &str, outcome| GroupRun {
+      group: name.into(),
+      inputs: Vec::new(),
+      outputs: vec![format!("out/{name}.eep").into()],
+      outcome,
+    };
+    let mut errors = ErrorList::default();
+    errors.add(Test, AppError::validation("Mario, \"bad\" file"));
+    let report = Report::new(
+      &[
+        group("Zelda", Outcome::Converted),
+        group("Kirby", Outcome::Unchanged),
+        group("Mario", Outcome::Failed("bad".into())),
+      ],
+      &errors,
+    );
+
+    assert_eq!(
+      report.render(Format::Csv),
+      "status,group,category,details\n\
+       converted,Zelda,,out/Zelda.eep\n\
+       unchanged,Kirby,,out/Kirby.eep\n\
+       error,,Test,\"Mario, \"\"bad\"\" file\"\n"
+    );
+
+    let json = serde_json::from_str::<serde_json::Value>(&report.render(Format::Json)).unwrap();
+    assert_eq!(json["groups"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["errors"][0]["category"], "Test");
+
+    let md = report.render(Format::Markdown);
+    assert!(md.contains("## Converted\n\n- Zelda: `out/Zelda.eep`\n"));
+    assert!(md.contains("## Skipped\n\n- Kirby (unchanged): `out/Kirby.eep`\n"));
+    assert!(md.contains("### Test\n\n- Mario, \"bad\" file\n"));
+
+    assert_eq!(Format::from_path(Path::new("a.JSON")), Some(Format::Json));
+    assert_eq!(Format::from_path(Path::new("a.md")), Some(Format::Markdown));
+    assert_eq!(Format::from_path(Path::new("a.txt")), None);
+
+    let dir = TestDir::new("report");
+    let written = report.export(&dir.join("report")).unwrap();
+    assert_eq!(written, dir.join("report.csv"));
+    assert!(std::fs::read_to_string(&written)
+      .unwrap()
+      .starts_with("status,group,category,details\n"));
+  }
+}
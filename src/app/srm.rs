@@ -0,0 +1,264 @@
+use super::{controller_pack, output::Staging};
+
+use ramp64_srm_convert_lib::{split::Params, Converter, UserParams};
+use std::ops::Range;
+
+pub(crate) const SRM_SIZE: usize = 0x48800;
+pub(crate) const CONTROLLER_PACKS_OFFSET: usize = 0x800;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Region {
+  Eeprom,
+  ControllerPack(usize),
+  Sram,
+  FlashRam,
+}
+
+impl Region {
+  pub(crate) const ALL: [Region; 7] = [
+    Region::Eeprom,
+    Region::ControllerPack(0),
+    Region::ControllerPack(1),
+    Region::ControllerPack(2),
+    Region::ControllerPack(3),
+    Region::Sram,
+    Region::FlashRam,
+  ];
+
+  pub(crate) fn range(&self) -> Range<usize> {
+    match self {
+      Region::Eeprom => 0..CONTROLLER_PACKS_OFFSET,
+      Region::ControllerPack(i) => {
+        let start = CONTROLLER_PACKS_OFFSET + i * controller_pack::PACK_SIZE;
+        start..start + controller_pack::PACK_SIZE
+      }
+      Region::Sram => 0x20800..0x28800,
+      Region::FlashRam => 0x28800..SRM_SIZE,
+    }
+  }
+
+  /// Gets the extension of the file split writes for this region
+  pub(crate) fn extension(&self, output_mupen: bool) -> String {
+    match self {
+      Region::Eeprom => "eep".into(),
+      Region::ControllerPack(_) if output_mupen => "mpk".into(),
+      Region::ControllerPack(i) => format!("mpk{}", i + 1),
+      Region::Sram => "sra".into(),
+      Region::FlashRam => "fla".into(),
+    }
+  }
+
+  fn is_battery(&self) -> bool {
+    !matches!(self, Region::ControllerPack(_))
+  }
+}
+
+impl std::fmt::Display for Region {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Region::Eeprom => f.write_str("EEPROM"),
+      Region::ControllerPack(i) => f.write_fmt(format_args!("Controller Pack {}", i + 1)),
+      Region::Sram => f.write_str("SRAM"),
+      Region::FlashRam => f.write_str("FlashRAM"),
+    }
+  }
+}
+
+/// Gets the largest size of an output file of the given type
+pub(crate) fn output_size(kind: &str) -> u64 {
+  let size = match kind {
+    "srm" => SRM_SIZE,
+    "eep" => Region::Eeprom.range().len(),
+    "mpk" => controller_pack::MUPEN_PACK_SIZE,
+    "sra" => Region::Sram.range().len(),
+    "fla" => Region::FlashRam.range().len(),
+    _ => controller_pack::PACK_SIZE,
+  };
+  size as u64
+}
+
+/// Swaps the byte order of every 32 bit word, as done for native EEPROM and FlashRAM files
+pub(crate) fn word_swap(buf: &mut [u8]) {
+  for word in buf.chunks_exact_mut(4) {
+    word.reverse();
+  }
+}
+
+/// Decompresses an RZip SRM with the converter library, which splits it into a temporary
+/// directory, and puts the regions back together.
+///
+/// The library leaves out empty regions, so these come back as they are in a new SRM.
+fn read_rzip(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+  let staging = Staging::new(&std::env::temp_dir())?;
+  Params::new(path)
+    .set_output_mupen_pack(true)
+    .set_out_dir(Some(staging.path()))
+    .set_name(Some("srm"))
+    .convert(&UserParams {
+      overwrite: true,
+      swap_bytes: false,
+    })
+    .map_err(|e| std::io::Error::new(e.kind(), e))?;
+
+  let mut data = vec![0xffu8; SRM_SIZE];
+  for i in 0..4 {
+    controller_pack::format(&mut data[Region::ControllerPack(i).range()]);
+  }
+  let files = staging.files().map_err(|e| e.error)?;
+  for (file, kind) in files {
+    let start = match kind.as_str() {
+      "eep" => Region::Eeprom.range().start,
+      "mpk" => CONTROLLER_PACKS_OFFSET,
+      "sra" => Region::Sram.range().start,
+      "fla" => Region::FlashRam.range().start,
+      _ => continue,
+    };
+    let bytes = std::fs::read(file)?;
+    data[start..start + bytes.len()].copy_from_slice(&bytes);
+  }
+  Ok(data)
+}
+
+/// Reads a whole SRM file, decompressing it if needed
+pub(crate) fn read(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+  let mut data = std::fs::read(path)?;
+  if data.starts_with(b"#RZIPv") {
+    data = read_rzip(path)?;
+  }
+  if data.len() != SRM_SIZE {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::InvalidData,
+      "invalid SRM file size",
+    ));
+  }
+  Ok(data)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum RegionState {
+  Empty,
+  Populated,
+  Suspicious(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RegionInfo {
+  pub region: Region,
+  pub state: RegionState,
+  /// Bytes which differ from the region fill value
+  pub used_bytes: usize,
+  /// If split writes a file for this region
+  pub split_output: bool,
+}
+
+/// Classifies every region of the SRM data
+pub(crate) fn analyze(data: &[u8]) -> Vec<RegionInfo> {
+  let mut infos = Region::ALL
+    .iter()
+    .map(|region| {
+      let bytes = &data[region.range()];
+      let zeros = bytes.iter().filter(|b| **b == 0x00).count();
+      let ones = bytes.iter().filter(|b| **b == 0xff).count();
+      let used_bytes = bytes.len() - zeros.max(ones);
+
+      let (state, split_output) = match region {
+        Region::ControllerPack(_) => {
+          let issues = controller_pack::verify(bytes);
+          let state = if used_bytes == 0
+            || (issues.is_empty() && controller_pack::read_notes(bytes).is_empty())
+          {
+            RegionState::Empty
+          } else if let Some(issue) = issues.first() {
+            RegionState::Suspicious(issue.to_string())
+          } else {
+            RegionState::Populated
+          };
+          (state, !controller_pack::is_blank(bytes))
+        }
+        _ if used_bytes == 0 => (RegionState::Empty, ones != bytes.len()),
+        _ => (RegionState::Populated, true),
+      };
+
+      RegionInfo {
+        region: *region,
+        state,
+        used_bytes,
+        split_output,
+      }
+    })
+    .collect::<Vec<_>>();
+
+  // a game only uses one kind of battery save
+  let batteries = infos
+    .iter()
+    .filter(|i| i.region.is_battery() && i.state != RegionState::Empty)
+    .count();
+  if batteries > 1 {
+    for info in infos
+      .iter_mut()
+      .filter(|i| i.region.is_battery() && i.state != RegionState::Empty)
+    {
+      info.state = RegionState::Suspicious("more than one battery region has data".into());
+    }
+  }
+
+  infos
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn new_srm() -> Vec<u8> {
+    let mut data = vec![0xffu8; SRM_SIZE];
+    for i in 0..4 {
+      controller_pack::format(&mut data[Region::ControllerPack(i).range()]);
+    }
+    data
+  }
+
+  #[test]
+  fn analyze_empty_srm() {
+    let infos = analyze(&new_srm());
+    assert_eq!(infos.len(), Region::ALL.len());
+    assert!(infos.iter().all(|i| i.state == RegionState::Empty));
+    assert!(infos.iter().all(|i| !i.split_output));
+  }
+
+  #[test]
+  fn analyze_populated_srm() {
+    let mut data = new_srm();
+    data[Region::Eeprom.range()][..0x10].fill(0x12);
+    data[Region::ControllerPack(1).range()][0x200] = 0x55;
+    data[Region::Sram.range()].fill(0x00);
+
+    let infos = analyze(&data);
+    assert_eq!(infos[0].state, RegionState::Populated);
+    assert_eq!(infos[0].used_bytes, 0x10);
+    assert!(matches!(infos[2].state, RegionState::Suspicious(_)));
+    // all zero regions are empty, but split still writes them
+    assert_eq!(infos[5].state, RegionState::Empty);
+    assert!(infos[5].split_output);
+    assert_eq!(infos[6].state, RegionState::Empty);
+    assert!(!infos[6].split_output);
+  }
+
+  #[test]
+  fn swap_words() {
+    let mut bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+    word_swap(&mut bytes);
+    assert_eq!(bytes, [4, 3, 2, 1, 8, 7, 6, 5]);
+  }
+
+  #[test]
+  fn analyze_multiple_batteries() {
+    let mut data = new_srm();
+    data[Region::Eeprom.range()][0] = 1;
+    data[Region::FlashRam.range()][0] = 1;
+
+    let infos = analyze(&data);
+    assert!(matches!(infos[0].state, RegionState::Suspicious(_)));
+    assert!(matches!(infos[6].state, RegionState::Suspicious(_)));
+    assert_eq!(infos[5].state, RegionState::Empty);
+  }
+}
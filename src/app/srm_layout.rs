@@ -0,0 +1,153 @@
+use super::{
+  srm::{self, RegionInfo, RegionState, SRM_SIZE},
+  work::GenericError,
+};
+
+/// Minimum share of the bar given to a region, so small ones stay visible
+const MIN_REGION_WIDTH: f32 = 0.06;
+
+#[derive(Debug)]
+pub(crate) struct SrmLayout {
+  path: std::path::PathBuf,
+  regions: Result<Vec<RegionInfo>, GenericError>,
+}
+
+impl SrmLayout {
+  pub fn new(path: std::path::PathBuf) -> Self {
+    let regions = srm::read(&path)
+      .map(|data| srm::analyze(&data))
+      .map_err(|e| GenericError::new(e, path.clone()));
+    Self { path, regions }
+  }
+
+  fn state_color(ui: &egui::Ui, state: &RegionState) -> egui::Color32 {
+    match state {
+      RegionState::Empty => ui.visuals().widgets.inactive.weak_bg_fill,
+      RegionState::Populated => egui::Color32::from_rgb(60, 150, 75),
+      RegionState::Suspicious(_) => ui.visuals().warn_fg_color,
+    }
+  }
+
+  fn show_bar(ui: &mut egui::Ui, regions: &[RegionInfo]) {
+    let weights = regions
+      .iter()
+      .map(|r| (r.region.range().len() as f32 / SRM_SIZE as f32).max(MIN_REGION_WIDTH))
+      .collect::<Vec<_>>();
+    let total = weights.iter().sum::<f32>();
+
+    let (rect, _) = ui.allocate_exact_size(
+      egui::vec2(ui.available_width(), 2.0 * ui.spacing().interact_size.y),
+      egui::Sense::hover(),
+    );
+
+    let mut left = rect.left();
+    for (info, weight) in regions.iter().zip(weights) {
+      let width = rect.width() * weight / total;
+      let region_rect = egui::Rect::from_min_max(
+        egui::pos2(left, rect.top()),
+        egui::pos2(left + width, rect.bottom()),
+      );
+      left += width;
+
+      ui.painter().rect_filled(
+        region_rect.shrink(1.0),
+        2.0,
+        Self::state_color(ui, &info.state),
+      );
+      ui.painter().text(
+        region_rect.center(),
+        egui::Align2::CENTER_CENTER,
+        match info.region {
+          srm::Region::ControllerPack(i) => format!("CP{}", i + 1),
+          region => region.to_string(),
+        },
+        egui::TextStyle::Small.resolve(ui.style()),
+        ui.visuals().strong_text_color(),
+      );
+
+      let mut hover = format!(
+        "{}\n{} of {} bytes used",
+        info.region,
+        info.used_bytes,
+        info.region.range().len()
+      );
+      if let RegionState::Suspicious(reason) = &info.state {
+        hover.push_str(&format!("\nSuspicious: {reason}"));
+      }
+      ui.interact(
+        region_rect,
+        ui.id().with(("srm_region", info.region)),
+        egui::Sense::hover(),
+      )
+      .on_hover_text(hover);
+    }
+  }
+
+  pub fn show(&self, ui: &mut egui::Ui, output_mupen: bool) {
+    ui.horizontal(|ui| {
+      ui.small("SRM");
+      ui.label(self.path.file_name().unwrap_or_default().to_string_lossy());
+    });
+
+    let regions = match &self.regions {
+      Ok(regions) => regions,
+      Err(error) => {
+        ui.colored_label(ui.visuals().error_fg_color, error.to_string());
+        return;
+      }
+    };
+
+    ui.add_space(3.0);
+    Self::show_bar(ui, regions);
+    ui.add_space(3.0);
+
+    egui::Grid::new("srm_layout")
+      .num_columns(5)
+      .striped(true)
+      .show(ui, |ui| {
+        ui.strong("Region");
+        ui.strong("Offset");
+        ui.strong("Used Bytes");
+        ui.strong("State");
+        ui.strong("Split Output");
+        ui.end_row();
+
+        let mut mupen_written = false;
+        for info in regions {
+          let range = info.region.range();
+          ui.label(info.region.to_string());
+          ui.monospace(format!("{:#07x}", range.start));
+          ui.label(format!("{} / {}", info.used_bytes, range.len()));
+          match &info.state {
+            RegionState::Empty => ui.weak("Empty"),
+            RegionState::Populated => ui.label("Populated"),
+            RegionState::Suspicious(reason) => ui
+              .colored_label(ui.visuals().warn_fg_color, "Suspicious")
+              .on_hover_text(reason),
+          };
+
+          let is_pack = matches!(info.region, srm::Region::ControllerPack(_));
+          let output = if !info.split_output || (is_pack && output_mupen && mupen_written) {
+            "—".to_string()
+          } else {
+            mupen_written |= is_pack;
+            let name = self
+              .path
+              .with_extension(info.region.extension(output_mupen));
+            name
+              .file_name()
+              .unwrap_or_default()
+              .to_string_lossy()
+              .into_owned()
+          };
+          ui.label(output);
+          ui.end_row();
+        }
+      });
+
+    if regions.iter().all(|r| !r.split_output) {
+      ui.add_space(3.0);
+      ui.label("This SRM has no save data, split will not produce any file.");
+    }
+  }
+}
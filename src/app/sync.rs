@@ -0,0 +1,641 @@
+use super::{
+  controller_pack::MUPEN_PACK_SIZE,
+  data_dir::data_dir,
+  error::AppError,
+  file_groups::GroupItem,
+  options::Options,
+  output::PlaceError,
+  overwrite::format_time,
+  srm::{self, Region, CONTROLLER_PACKS_OFFSET},
+  work::GenericError,
+  ErrorCategory,
+};
+
+use crate::widgets::browser::{Browse, UiBrowser};
+
+use ramp64_srm_convert_lib::UserParams;
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Directory in the data directory keeping when each group was last synced, per pair of folders
+const STATE_DIR: &str = "sync";
+
+const NATIVE_EXTENSIONS: [&str; 8] = ["eep", "sra", "fla", "mpk", "mpk1", "mpk2", "mpk3", "mpk4"];
+
+/// What the sync found for a pair, before the user reviews it
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Plan {
+  InSync,
+  ToNative,
+  ToRetroArch,
+  /// Both sides changed since the last sync
+  Conflict,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+  /// Split the SRM into the native folder
+  ToNative,
+  /// Create the SRM in the RetroArch folder from the native saves
+  ToRetroArch,
+}
+
+impl std::fmt::Display for Direction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Direction::ToNative => f.write_str("RetroArch → Native"),
+      Direction::ToRetroArch => f.write_str("Native → RetroArch"),
+    }
+  }
+}
+
+/// Decides the direction of a pair with saves on both sides from what changed since the last sync
+fn decide(
+  srm_time: Option<SystemTime>,
+  native_time: Option<SystemTime>,
+  same_contents: bool,
+  last_sync: Option<SystemTime>,
+) -> Plan {
+  let (Some(srm_time), Some(native_time)) = (srm_time, native_time) else {
+    // without the times of both sides, the user decides
+    return Plan::Conflict;
+  };
+  if same_contents {
+    return Plan::InSync;
+  }
+  if let Some(last_sync) = last_sync {
+    match (srm_time > last_sync, native_time > last_sync) {
+      (true, true) => return Plan::Conflict,
+      (true, false) => return Plan::ToNative,
+      (false, true) => return Plan::ToRetroArch,
+      // neither changed since, so the newest side wins
+      (false, false) => {}
+    }
+  }
+  match srm_time.cmp(&native_time) {
+    std::cmp::Ordering::Greater => Plan::ToNative,
+    std::cmp::Ordering::Less => Plan::ToRetroArch,
+    std::cmp::Ordering::Equal => Plan::Conflict,
+  }
+}
+
+/// Gets the range of the SRM held by a native save type
+fn native_range(kind: &str) -> Option<Range<usize>> {
+  match kind {
+    "eep" => Some(Region::Eeprom.range()),
+    "sra" => Some(Region::Sram.range()),
+    "fla" => Some(Region::FlashRam.range()),
+    "mpk" => Some(CONTROLLER_PACKS_OFFSET..CONTROLLER_PACKS_OFFSET + MUPEN_PACK_SIZE),
+    _ => {
+      let player = kind.strip_prefix("mpk")?.parse::<usize>().ok()?;
+      (1..=4)
+        .contains(&player)
+        .then(|| Region::ControllerPack(player - 1).range())
+    }
+  }
+}
+
+fn kind(path: &Path) -> Option<String> {
+  Some(path.extension()?.to_str()?.to_ascii_lowercase())
+}
+
+/// Checks if the native saves hold the same data as the SRM, in any byte order
+fn same_contents(srm: &Path, natives: &[PathBuf]) -> bool {
+  let Ok(data) = srm::read(srm) else {
+    return false;
+  };
+  let mut covered = Vec::new();
+  for native in natives {
+    let Some(range) = kind(native).as_deref().and_then(native_range) else {
+      continue;
+    };
+    let Ok(mut native) = std::fs::read(native) else {
+      return false;
+    };
+    let Some(region) = data.get(range.start..range.start + native.len().min(range.len())) else {
+      return false;
+    };
+    let native = &mut native[..region.len()];
+    if region != native {
+      srm::word_swap(native);
+      if region != native {
+        return false;
+      }
+    }
+    covered.push(range);
+  }
+  // the SRM must not hold saves missing from the native folder
+  srm::analyze(&data)
+    .iter()
+    .filter(|info| info.split_output)
+    .all(|info| {
+      let range = info.region.range();
+      covered
+        .iter()
+        .any(|c| c.start <= range.start && range.end <= c.end)
+    })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+  path.metadata().and_then(|m| m.modified()).ok()
+}
+
+/// Gets the file keeping the sync state of the two folders.
+///
+/// The file is named after a hash of the folders, which must not change between versions.
+fn state_path(retroarch_dir: &Path, native_dir: &Path) -> std::io::Result<PathBuf> {
+  // FNV-1a
+  let mut hash = 0xcbf29ce484222325u64;
+  for byte in [retroarch_dir, Path::new("\0"), native_dir]
+    .iter()
+    .flat_map(|dir| dir.as_os_str().to_string_lossy().into_owned().into_bytes())
+  {
+    hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+  }
+  let dir = data_dir()?.join(STATE_DIR);
+  std::fs::create_dir_all(&dir)?;
+  Ok(dir.join(format!("{hash:016x}.tsv")))
+}
+
+fn load_state(path: &Path) -> HashMap<String, SystemTime> {
+  let Ok(state) = std::fs::read_to_string(path) else {
+    return HashMap::new();
+  };
+  state
+    .lines()
+    .filter_map(|line| {
+      let (secs, group) = line.split_once('\t')?;
+      let time = UNIX_EPOCH + Duration::from_secs(secs.parse().ok()?);
+      Some((group.to_string(), time))
+    })
+    .collect()
+}
+
+fn save_state(path: &Path, state: &HashMap<String, SystemTime>) -> Result<(), GenericError> {
+  let mut lines = state
+    .iter()
+    .map(|(group, time)| {
+      let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+      format!("{secs}\t{group}\n")
+    })
+    .collect::<Vec<_>>();
+  lines.sort();
+  std::fs::write(path, lines.concat()).map_err(|e| GenericError::new(e, path.to_path_buf()))
+}
+
+/// Lists the save files directly inside the directory, by group
+fn scan(dir: &Path, extensions: &[&str]) -> Result<BTreeMap<String, Vec<PathBuf>>, GenericError> {
+  let entries = std::fs::read_dir(dir).map_err(|e| GenericError::new(e, dir.to_path_buf()))?;
+  let mut groups: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+  for path in entries.flatten().map(|e| e.path()) {
+    if !path.is_file() || !kind(&path).is_some_and(|k| extensions.contains(&k.as_str())) {
+      continue;
+    }
+    if let Some(stem) = path.file_stem() {
+      groups
+        .entry(stem.to_string_lossy().into_owned())
+        .or_default()
+        .push(path);
+    }
+  }
+  Ok(groups)
+}
+
+/// An SRM and the native saves of the same group
+#[derive(Debug)]
+pub(crate) struct Pair {
+  group: String,
+  srm: Option<PathBuf>,
+  natives: Vec<PathBuf>,
+  plan: Plan,
+  /// Direction chosen in the review, or `None` to leave the pair alone
+  direction: Option<Direction>,
+  result: Option<Result<(), String>>,
+}
+
+impl Pair {
+  fn new(
+    group: String,
+    srm: Option<PathBuf>,
+    natives: Vec<PathBuf>,
+    last_sync: Option<SystemTime>,
+  ) -> Self {
+    let srm_time = srm.as_deref().and_then(modified);
+    let native_time = natives.iter().filter_map(|p| modified(p)).max();
+    let same = srm
+      .as_deref()
+      .is_some_and(|srm| !natives.is_empty() && same_contents(srm, &natives));
+    let plan = match (&srm, natives.is_empty()) {
+      (None, _) => Plan::ToRetroArch,
+      (_, true) => Plan::ToNative,
+      _ => decide(srm_time, native_time, same, last_sync),
+    };
+    Self {
+      group,
+      srm,
+      natives,
+      plan,
+      direction: match plan {
+        Plan::ToNative => Some(Direction::ToNative),
+        Plan::ToRetroArch => Some(Direction::ToRetroArch),
+        Plan::InSync | Plan::Conflict => None,
+      },
+      result: None,
+    }
+  }
+
+  fn newest_native(&self) -> Option<SystemTime> {
+    self.natives.iter().filter_map(|p| modified(p)).max()
+  }
+
+  /// Converts the pair in the chosen direction
+  fn sync(
+    &self,
+    direction: Direction,
+    dirs: (&Path, &Path),
+    options: &Options,
+  ) -> Result<(), AppError> {
+    let (retroarch_dir, native_dir) = dirs;
+    let (mut item, output_dir) = match direction {
+      Direction::ToNative => {
+        let srm = self
+          .srm
+          .clone()
+          .ok_or_else(|| AppError::validation("there is no SRM to sync from"))?;
+        (GroupItem::from(srm), native_dir)
+      }
+      Direction::ToRetroArch => {
+        let mut natives = self.natives.iter().cloned();
+        let native = natives
+          .next()
+          .ok_or_else(|| AppError::validation("there are no native saves to sync from"))?;
+        let mut item = GroupItem::from(native);
+        natives.for_each(|native| item.set(native));
+        (item, retroarch_dir)
+      }
+    };
+    item.detect_byte_order();
+    let options = Options {
+      user_params: UserParams {
+        overwrite: true,
+        ..options.user_params
+      },
+      ask_overwrite: false,
+      output_dir: Some(output_dir.to_path_buf()),
+      name_template: String::new(),
+      mirror_dirs: false,
+      // both sides keep the same time, so the next sync sees no change
+      preserve_times: true,
+      skip_identical: false,
+      ..options.clone()
+    };
+    let (staged, _) = item.stage(&options).map_err(|(error, _)| *error)?;
+    match staged.place() {
+      Ok(_) | Err(PlaceError { error: None, .. }) => Ok(()),
+      Err(PlaceError {
+        error: Some(error), ..
+      }) => Err(error.into()),
+    }
+  }
+}
+
+/// Work for the worker thread, as reading and converting the saves takes a while
+#[derive(Debug)]
+pub(crate) enum Job {
+  /// Pairs the saves of both folders and plans their directions
+  Pair {
+    retroarch_dir: PathBuf,
+    native_dir: PathBuf,
+  },
+  /// Syncs every pair with a chosen direction
+  Run {
+    retroarch_dir: PathBuf,
+    native_dir: PathBuf,
+    pairs: Vec<Pair>,
+    options: Options,
+  },
+}
+
+/// The result of a sync job
+#[derive(Debug)]
+pub(crate) enum Done {
+  Paired(Result<Vec<Pair>, GenericError>),
+  Synced {
+    pairs: Vec<Pair>,
+    errors: Vec<(ErrorCategory, AppError)>,
+  },
+}
+
+impl Job {
+  pub fn run(self) -> Done {
+    match self {
+      Job::Pair {
+        retroarch_dir,
+        native_dir,
+      } => {
+        log::info!(
+          "Pairing the saves of {} and {}",
+          retroarch_dir.display(),
+          native_dir.display()
+        );
+        Done::Paired(pair(&retroarch_dir, &native_dir))
+      }
+      Job::Run {
+        retroarch_dir,
+        native_dir,
+        mut pairs,
+        options,
+      } => {
+        let errors = run(&retroarch_dir, &native_dir, &mut pairs, &options);
+        Done::Synced { pairs, errors }
+      }
+    }
+  }
+}
+
+fn pair(retroarch_dir: &Path, native_dir: &Path) -> Result<Vec<Pair>, GenericError> {
+  let mut srms = scan(retroarch_dir, &["srm"])?;
+  let mut natives = scan(native_dir, &NATIVE_EXTENSIONS)?;
+  let state = state_path(retroarch_dir, native_dir)
+    .map(|path| load_state(&path))
+    .unwrap_or_default();
+
+  let mut groups = srms
+    .keys()
+    .chain(natives.keys())
+    .cloned()
+    .collect::<Vec<_>>();
+  groups.sort();
+  groups.dedup();
+  Ok(
+    groups
+      .into_iter()
+      .map(|group| {
+        let srm = srms.remove(&group).and_then(|mut s| s.pop());
+        let natives = natives.remove(&group).unwrap_or_default();
+        let last_sync = state.get(&group).copied();
+        Pair::new(group, srm, natives, last_sync)
+      })
+      .collect(),
+  )
+}
+
+/// Syncs every pair with a chosen direction, returning the errors found
+fn run(
+  retroarch_dir: &Path,
+  native_dir: &Path,
+  pairs: &mut [Pair],
+  options: &Options,
+) -> Vec<(ErrorCategory, AppError)> {
+  let mut errors = Vec::new();
+  let state_path = match state_path(retroarch_dir, native_dir) {
+    Ok(path) => Some(path),
+    Err(error) => {
+      let error = AppError::io(error, PathBuf::new());
+      errors.push((ErrorCategory::of(&error, ErrorCategory::Output), error));
+      None
+    }
+  };
+  let mut state = state_path.as_deref().map(load_state).unwrap_or_default();
+  let now = SystemTime::now();
+  for pair in pairs.iter_mut() {
+    let Some(direction) = pair.direction else {
+      if pair.plan == Plan::InSync {
+        state.insert(pair.group.clone(), now);
+      }
+      continue;
+    };
+    match pair.sync(direction, (retroarch_dir, native_dir), options) {
+      Ok(()) => {
+        log::info!("Synced {}: {direction}", pair.group);
+        state.insert(pair.group.clone(), now);
+        pair.result = Some(Ok(()));
+      }
+      Err(error) => {
+        pair.result = Some(Err(error.to_string()));
+        let error = error.in_group(pair.group.clone());
+        errors.push((ErrorCategory::of(&error, ErrorCategory::Conversion), error));
+      }
+    }
+    pair.direction = None;
+  }
+  if let Some(Err(error)) = state_path.map(|path| save_state(&path, &state)) {
+    let error = AppError::from(error);
+    errors.push((ErrorCategory::of(&error, ErrorCategory::Output), error));
+  }
+  errors
+}
+
+/// Syncs the saves of a RetroArch folder with the ones of a standalone emulator
+#[derive(Debug, Default)]
+pub(crate) struct Sync {
+  retroarch_dir: Option<PathBuf>,
+  native_dir: Option<PathBuf>,
+  pairs: Vec<Pair>,
+  scan_error: Option<String>,
+  /// A job was sent to the worker, and its result has not come back yet
+  busy: bool,
+}
+
+impl Sync {
+  fn rescan(&mut self) -> Option<Job> {
+    self.pairs.clear();
+    self.scan_error = None;
+    let (Some(retroarch_dir), Some(native_dir)) = (&self.retroarch_dir, &self.native_dir) else {
+      return None;
+    };
+    self.busy = true;
+    Some(Job::Pair {
+      retroarch_dir: retroarch_dir.clone(),
+      native_dir: native_dir.clone(),
+    })
+  }
+
+  fn sync(&mut self, options: &Options) -> Option<Job> {
+    let (Some(retroarch_dir), Some(native_dir)) = (&self.retroarch_dir, &self.native_dir) else {
+      return None;
+    };
+    self.busy = true;
+    Some(Job::Run {
+      retroarch_dir: retroarch_dir.clone(),
+      native_dir: native_dir.clone(),
+      pairs: std::mem::take(&mut self.pairs),
+      options: options.clone(),
+    })
+  }
+
+  /// Takes the result of a job, returning the errors found syncing
+  pub fn finish(&mut self, done: Done) -> Vec<(ErrorCategory, AppError)> {
+    self.busy = false;
+    match done {
+      Done::Paired(Ok(pairs)) => self.pairs = pairs,
+      Done::Paired(Err(error)) => self.scan_error = Some(error.to_string()),
+      Done::Synced { pairs, errors } => {
+        self.pairs = pairs;
+        return errors;
+      }
+    }
+    Vec::new()
+  }
+
+  fn show_pairs(ui: &mut egui::Ui, pairs: &mut [Pair]) {
+    let time = |time: Option<SystemTime>| time.map_or("Missing".into(), format_time);
+    egui::Grid::new("sync_pairs")
+      .num_columns(5)
+      .striped(true)
+      .show(ui, |ui| {
+        ui.strong("Group");
+        ui.strong("RetroArch");
+        ui.strong("Native");
+        ui.strong("Found");
+        ui.strong("Direction");
+        ui.end_row();
+
+        for (i, pair) in pairs.iter_mut().enumerate() {
+          ui.label(&pair.group);
+          ui.label(time(pair.srm.as_deref().and_then(modified)));
+          ui.label(time(pair.newest_native()));
+          match pair.plan {
+            Plan::InSync => ui.weak("In sync"),
+            Plan::ToNative => ui.label("RetroArch changed"),
+            Plan::ToRetroArch => ui.label("Native changed"),
+            Plan::Conflict => ui.colored_label(ui.visuals().warn_fg_color, "Conflict"),
+          }
+          .on_hover_text(match pair.plan {
+            Plan::InSync => "Both sides hold the same saves",
+            Plan::Conflict => "Both sides changed since the last sync, pick which one to keep",
+            _ => "The newest side is copied over the other",
+          });
+          match &pair.result {
+            Some(Ok(())) => {
+              ui.label("Synced");
+            }
+            Some(Err(error)) => {
+              ui.colored_label(ui.visuals().error_fg_color, "Failed")
+                .on_hover_text(error);
+            }
+            None => {
+              egui::ComboBox::from_id_source(("sync_direction", i))
+                .selected_text(pair.direction.map_or("Skip".into(), |d| d.to_string()))
+                .show_ui(ui, |ui| {
+                  ui.selectable_value(&mut pair.direction, None, "Skip");
+                  if pair.srm.is_some() {
+                    let to_native = Some(Direction::ToNative);
+                    ui.selectable_value(&mut pair.direction, to_native, "RetroArch → Native");
+                  }
+                  if !pair.natives.is_empty() {
+                    let to_retroarch = Some(Direction::ToRetroArch);
+                    ui.selectable_value(&mut pair.direction, to_retroarch, "Native → RetroArch");
+                  }
+                });
+            }
+          }
+          ui.end_row();
+        }
+      });
+  }
+
+  /// Shows the folders and the planned directions, returning the job to send to the worker
+  pub fn show(&mut self, ui: &mut egui::Ui, options: &Options) -> Option<Job> {
+    let mut changed = false;
+    ui.add_enabled_ui(!self.busy, |ui| {
+      egui::Grid::new("sync_dirs").num_columns(2).show(ui, |ui| {
+        ui.label("RetroArch Saves");
+        changed |= ui
+          .browse(&mut self.retroarch_dir, Browse::pick_directory())
+          .changed();
+        ui.end_row();
+        ui.label("Native Saves");
+        changed |= ui
+          .browse(&mut self.native_dir, Browse::pick_directory())
+          .changed();
+        ui.end_row();
+      });
+    });
+    let mut job = None;
+    if changed {
+      job = self.rescan();
+    }
+
+    ui.separator();
+    match &self.scan_error {
+      _ if self.busy => {
+        ui.horizontal(|ui| {
+          ui.spinner();
+          ui.label("Working...");
+        });
+      }
+      Some(error) => {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+      }
+      None if self.pairs.is_empty() => {
+        ui.label("Select both folders to find the saves to sync.");
+      }
+      None => {
+        Self::show_pairs(ui, &mut self.pairs);
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+          if ui.button("Rescan").clicked() {
+            job = self.rescan();
+          }
+          if ui.button("Sync").clicked() {
+            job = self.sync(options);
+          }
+        });
+      }
+    }
+    job
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decide_directions() {
+    let t = |secs| Some(UNIX_EPOCH + Duration::from_secs(secs));
+    assert_eq!(decide(None, t(1), false, None), Plan::Conflict);
+    assert_eq!(decide(t(1), None, false, None), Plan::Conflict);
+    assert_eq!(decide(t(1), t(2), true, None), Plan::InSync);
+    assert_eq!(decide(t(1), t(2), false, None), Plan::ToRetroArch);
+    assert_eq!(decide(t(3), t(2), false, None), Plan::ToNative);
+    assert_eq!(decide(t(3), t(2), false, t(1)), Plan::Conflict);
+    assert_eq!(decide(t(1), t(3), false, t(2)), Plan::ToRetroArch);
+    assert_eq!(decide(t(3), t(1), false, t(2)), Plan::ToNative);
+  }
+
+  #[test]
+  fn pair_without_natives() {
+    // the SRM time is unknown, as the file does not exist
+    let pair = Pair::new(
+      "Zelda".into(),
+      Some("missing/Zelda.srm".into()),
+      Vec::new(),
+      None,
+    );
+    assert_eq!(pair.plan, Plan::ToNative);
+    let dirs = (Path::new("missing"), Path::new("missing"));
+    assert!(pair
+      .sync(Direction::ToRetroArch, dirs, &Options::default())
+      .is_err());
+  }
+
+  #[test]
+  fn native_ranges() {
+    assert_eq!(native_range("eep"), Some(Region::Eeprom.range()));
+    assert_eq!(
+      native_range("mpk2"),
+      Some(Region::ControllerPack(1).range())
+    );
+    assert_eq!(native_range("mpk").map(|r| r.len()), Some(MUPEN_PACK_SIZE));
+    assert_eq!(native_range("mpk5"), None);
+    assert_eq!(native_range("srm"), None);
+  }
+}
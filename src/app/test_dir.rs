@@ -0,0 +1,35 @@
+use std::path::{Path, PathBuf};
+
+/// Directory for the files of a test, removed with them once dropped, even when the test fails
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+  /// Creates an empty directory, unique to the test process
+  pub fn new(name: &str) -> Self {
+    let dir = std::env::temp_dir().join(format!("ramp64-{name}-{}", std::process::id()));
+    // left by an earlier run which got the same process id
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    Self(dir)
+  }
+}
+
+impl std::ops::Deref for TestDir {
+  type Target = Path;
+
+  fn deref(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl AsRef<Path> for TestDir {
+  fn as_ref(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl Drop for TestDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
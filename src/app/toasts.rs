@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays once the pointer leaves it
+const DURATION: Duration = Duration::from_secs(5);
+
+/// Most toasts shown at once, older ones are dropped
+const MAX_TOASTS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Level {
+  Info,
+  Error,
+}
+
+struct Toast<T> {
+  message: String,
+  level: Level,
+  /// What to open when the toast is clicked
+  details: Option<T>,
+  until: Instant,
+}
+
+/// Short lived messages for routine results, which do not block the rest of the window
+pub(crate) struct Toasts<T> {
+  toasts: VecDeque<Toast<T>>,
+}
+
+impl<T> Default for Toasts<T> {
+  fn default() -> Self {
+    Self {
+      toasts: VecDeque::new(),
+    }
+  }
+}
+
+impl<T> Toasts<T> {
+  pub fn info(&mut self, message: impl Into<String>, details: Option<T>) {
+    self.push(message.into(), Level::Info, details);
+  }
+
+  pub fn error(&mut self, message: impl Into<String>, details: Option<T>) {
+    self.push(message.into(), Level::Error, details);
+  }
+
+  fn push(&mut self, message: String, level: Level, details: Option<T>) {
+    if self.toasts.len() == MAX_TOASTS {
+      self.toasts.pop_front();
+    }
+    self.toasts.push_back(Toast {
+      message,
+      level,
+      details,
+      until: Instant::now() + DURATION,
+    });
+  }
+
+  fn expire(&mut self, now: Instant) {
+    self.toasts.retain(|toast| toast.until > now);
+  }
+
+  /// Shows the toasts in the bottom right corner, returning the details of the one clicked
+  pub fn show(&mut self, ctx: &egui::Context) -> Option<T> {
+    let now = Instant::now();
+    self.expire(now);
+    if self.toasts.is_empty() {
+      return None;
+    }
+
+    let mut clicked = None;
+    let mut dismissed = None;
+    egui::Area::new("toasts")
+      .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+      .order(egui::Order::Foreground)
+      .show(ctx, |ui| {
+        for (i, toast) in self.toasts.iter_mut().enumerate() {
+          let response = egui::Frame::popup(ui.style())
+            .show(ui, |ui| {
+              ui.horizontal(|ui| {
+                match toast.level {
+                  Level::Info => ui.label(&toast.message),
+                  Level::Error => ui.colored_label(ui.visuals().error_fg_color, &toast.message),
+                };
+                if ui.small_button("✖").clicked() {
+                  dismissed = Some(i);
+                }
+              });
+            })
+            .response
+            .interact(egui::Sense::click());
+          if response.hovered() {
+            toast.until = toast.until.max(now + DURATION);
+          }
+          if toast.details.is_some() {
+            if response.clicked() {
+              clicked = Some(i);
+            }
+            response.on_hover_text("Click to show the details");
+          }
+        }
+      });
+
+    // the dismiss button is inside the clickable toast
+    if let Some(i) = dismissed {
+      self.toasts.remove(i);
+    } else if let Some(i) = clicked {
+      return self.toasts.remove(i).and_then(|toast| toast.details);
+    }
+    if let Some(until) = self.toasts.iter().map(|toast| toast.until).min() {
+      ctx.request_repaint_after(until.saturating_duration_since(now));
+    }
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn expire_and_drop_old() {
+    let mut toasts = Toasts::default();
+    for i in 0..MAX_TOASTS + 2 {
+      toasts.info(format!("{i} files added"), Some(i));
+    }
+    assert_eq!(toasts.toasts.len(), MAX_TOASTS);
+    assert_eq!(toasts.toasts.front().and_then(|t| t.details), Some(2));
+
+    toasts.error("scan failed", None);
+    toasts.expire(Instant::now());
+    assert_eq!(toasts.toasts.len(), MAX_TOASTS);
+    toasts.expire(Instant::now() + DURATION);
+    assert!(toasts.toasts.is_empty());
+  }
+}
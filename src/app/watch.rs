@@ -0,0 +1,389 @@
+use super::overwrite::format_time;
+
+use crate::widgets::{
+  browser::{Browse, UiBrowser},
+  trim_label::UiTrimLabel,
+};
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Time without new changes before the changed files are converted
+pub(crate) const DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Most entries kept in the activity log
+const MAX_ACTIVITY: usize = 500;
+
+/// Files written to the watched directories
+#[derive(Debug, Default)]
+pub(crate) struct Changes {
+  pub files: Vec<PathBuf>,
+  /// Changes were dropped, so every file of the directories is listed instead
+  pub overflowed: bool,
+}
+
+/// Lists the files of the directory and its subdirectories, calling `visit` on each directory
+fn walk_dir(
+  dir: &std::path::Path,
+  files: &mut Vec<PathBuf>,
+  visit: &mut impl FnMut(&std::path::Path) -> Result<(), super::work::GenericError>,
+) -> Result<(), super::work::GenericError> {
+  visit(dir)?;
+  let entries =
+    std::fs::read_dir(dir).map_err(|e| super::work::GenericError::new(e, dir.to_path_buf()))?;
+  for entry in entries.flatten() {
+    if entry.file_type().is_ok_and(|t| t.is_dir()) {
+      // hidden directories include the conversion staging ones
+      if !is_hidden(&entry.file_name()) {
+        walk_dir(&entry.path(), files, visit)?;
+      }
+    } else {
+      files.push(entry.path());
+    }
+  }
+  Ok(())
+}
+
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+  name.to_string_lossy().starts_with('.')
+}
+
+/// Watches directories and their subdirectories for written files, through inotify
+#[cfg(target_os = "linux")]
+pub(crate) struct Watcher {
+  fd: std::os::fd::OwnedFd,
+  roots: Vec<PathBuf>,
+  dirs: std::collections::HashMap<i32, PathBuf>,
+}
+
+#[cfg(target_os = "linux")]
+impl Watcher {
+  pub fn new(dirs: &[PathBuf]) -> Result<Self, super::work::GenericError> {
+    use super::work::GenericError;
+    use std::os::fd::FromRawFd;
+
+    let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+    if fd < 0 {
+      return Err(GenericError::new(
+        std::io::Error::last_os_error(),
+        PathBuf::new(),
+      ));
+    }
+    // SAFETY: the descriptor was just opened, and nothing else owns it
+    let fd = unsafe { std::os::fd::OwnedFd::from_raw_fd(fd) };
+    let mut watcher = Self {
+      fd,
+      roots: dirs.to_vec(),
+      dirs: Default::default(),
+    };
+    for dir in dirs {
+      watcher.add_tree(dir, &mut Vec::new())?;
+    }
+    Ok(watcher)
+  }
+
+  /// Watches the directory and its subdirectories, listing the files already in them
+  fn add_tree(
+    &mut self,
+    dir: &std::path::Path,
+    files: &mut Vec<PathBuf>,
+  ) -> Result<(), super::work::GenericError> {
+    use super::work::GenericError;
+    use std::os::unix::ffi::OsStrExt;
+
+    let fd = std::os::fd::AsRawFd::as_raw_fd(&self.fd);
+    walk_dir(dir, files, &mut |dir| {
+      let path = std::ffi::CString::new(dir.as_os_str().as_bytes())
+        .map_err(|e| GenericError::new(e.into(), dir.to_path_buf()))?;
+      let wd = unsafe {
+        libc::inotify_add_watch(
+          fd,
+          path.as_ptr(),
+          libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE,
+        )
+      };
+      if wd < 0 {
+        return Err(GenericError::new(
+          std::io::Error::last_os_error(),
+          dir.to_path_buf(),
+        ));
+      }
+      self.dirs.insert(wd, dir.to_path_buf());
+      Ok(())
+    })
+  }
+
+  /// Waits for files to be written, returning the ones written before the timeout
+  pub fn wait(&mut self, timeout: Duration) -> std::io::Result<Changes> {
+    use std::os::fd::AsRawFd;
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut poll = libc::pollfd {
+      fd: self.fd.as_raw_fd(),
+      events: libc::POLLIN,
+      revents: 0,
+    };
+    let ready = unsafe { libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) };
+    if ready < 0 {
+      let error = std::io::Error::last_os_error();
+      return match error.kind() {
+        std::io::ErrorKind::Interrupted => Ok(Changes::default()),
+        _ => Err(error),
+      };
+    }
+
+    let mut changes = Changes::default();
+    let mut new_dirs = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+      let read = unsafe { libc::read(self.fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+      if read <= 0 {
+        break;
+      }
+      let mut offset = 0;
+      let header = std::mem::size_of::<libc::inotify_event>();
+      while offset + header <= read as usize {
+        // SAFETY: the kernel writes whole events, which may not be aligned in the buffer
+        let event: libc::inotify_event =
+          unsafe { std::ptr::read_unaligned(buf[offset..].as_ptr().cast()) };
+        let name = &buf[offset + header..offset + header + event.len as usize];
+        let name = &name[..name.iter().position(|b| *b == 0).unwrap_or(name.len())];
+        let name = std::ffi::OsStr::from_bytes(name);
+        if event.mask & libc::IN_Q_OVERFLOW != 0 {
+          changes.overflowed = true;
+        } else if let Some(dir) = self.dirs.get(&event.wd) {
+          if event.mask & libc::IN_ISDIR != 0 {
+            // new subdirectories are watched too, and their files count as written
+            if event.mask & (libc::IN_CREATE | libc::IN_MOVED_TO) != 0 && !is_hidden(name) {
+              new_dirs.push(dir.join(name));
+            }
+          } else if event.mask & libc::IN_CREATE == 0 && !name.is_empty() {
+            changes.files.push(dir.join(name));
+          }
+        }
+        offset += header + event.len as usize;
+      }
+    }
+
+    if changes.overflowed {
+      // the watches are kept, but the dropped events may have added directories
+      changes.files.clear();
+      new_dirs = self.roots.clone();
+    }
+    for dir in new_dirs {
+      self
+        .add_tree(&dir, &mut changes.files)
+        .map_err(|e| e.error)?;
+    }
+    Ok(changes)
+  }
+}
+
+/// Watches directories and their subdirectories for written files, comparing their modification
+/// times
+#[cfg(not(target_os = "linux"))]
+pub(crate) struct Watcher {
+  dirs: Vec<PathBuf>,
+  snapshot: std::collections::HashMap<PathBuf, SystemTime>,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Watcher {
+  pub fn new(dirs: &[PathBuf]) -> Result<Self, super::work::GenericError> {
+    let mut watcher = Self {
+      dirs: dirs.to_vec(),
+      snapshot: Default::default(),
+    };
+    watcher.snapshot = watcher.scan()?;
+    Ok(watcher)
+  }
+
+  fn scan(
+    &self,
+  ) -> Result<std::collections::HashMap<PathBuf, SystemTime>, super::work::GenericError> {
+    let mut files = Vec::new();
+    for dir in &self.dirs {
+      walk_dir(dir, &mut files, &mut |_| Ok(()))?;
+    }
+    Ok(
+      files
+        .into_iter()
+        .filter_map(|file| {
+          let modified = file.metadata().and_then(|m| m.modified()).ok()?;
+          Some((file, modified))
+        })
+        .collect(),
+    )
+  }
+
+  /// Waits for files to be written, returning the ones written before the timeout
+  pub fn wait(&mut self, timeout: Duration) -> std::io::Result<Changes> {
+    std::thread::sleep(timeout);
+    let snapshot = self.scan().map_err(|e| e.error)?;
+    let files = snapshot
+      .iter()
+      .filter(|(path, modified)| self.snapshot.get(*path) != Some(modified))
+      .map(|(path, _)| path.clone())
+      .collect();
+    self.snapshot = snapshot;
+    Ok(Changes {
+      files,
+      overflowed: false,
+    })
+  }
+}
+
+#[derive(Debug)]
+struct Activity {
+  time: SystemTime,
+  message: String,
+  error: bool,
+}
+
+/// Folders to watch, and what happened while watching them
+#[derive(Debug, Default)]
+pub(crate) struct Watch {
+  dirs: Vec<PathBuf>,
+  new_dir: Option<PathBuf>,
+  active: bool,
+  log: VecDeque<Activity>,
+}
+
+impl Watch {
+  pub fn is_active(&self) -> bool {
+    self.active
+  }
+
+  pub fn has_activity(&self) -> bool {
+    self.active || !self.log.is_empty()
+  }
+
+  pub fn set_active(&mut self, active: bool) {
+    self.active = active;
+  }
+
+  pub fn log(&mut self, message: impl Into<String>) {
+    self.push(message.into(), false);
+  }
+
+  pub fn log_error(&mut self, message: impl Into<String>) {
+    self.push(message.into(), true);
+  }
+
+  fn push(&mut self, message: String, error: bool) {
+    match error {
+      true => log::warn!("Watch: {message}"),
+      false => log::info!("Watch: {message}"),
+    }
+    if self.log.len() == MAX_ACTIVITY {
+      self.log.pop_front();
+    }
+    self.log.push_back(Activity {
+      time: SystemTime::now(),
+      message,
+      error,
+    });
+  }
+
+  /// Shows the watched folders, returning the folders to watch once started, or none once stopped
+  pub fn show(&mut self, ui: &mut egui::Ui) -> Option<Vec<PathBuf>> {
+    ui.label(
+      "Saves written to these folders and their subfolders are converted with the current options.",
+    );
+    ui.label("Existing outputs are overwritten, unless identical or newer than the saves.");
+    ui.add_space(3.0);
+
+    let mut remove = None;
+    egui::Grid::new("watch_dirs")
+      .num_columns(2)
+      .striped(true)
+      .show(ui, |ui| {
+        for (i, dir) in self.dirs.iter().enumerate() {
+          ui.trim_label(dir.to_string_lossy(), false);
+          if ui
+            .add_enabled(!self.active, egui::Button::new("✖"))
+            .on_hover_text("Stop watching this folder")
+            .clicked()
+          {
+            remove = Some(i);
+          }
+          ui.end_row();
+        }
+      });
+    if let Some(i) = remove {
+      self.dirs.remove(i);
+    }
+
+    ui.add_enabled_ui(!self.active, |ui| {
+      ui.horizontal(|ui| {
+        ui.label("Add Folder");
+        if ui
+          .browse(&mut self.new_dir, Browse::pick_directory())
+          .changed()
+        {
+          if let Some(dir) = self.new_dir.take() {
+            if !self.dirs.contains(&dir) {
+              self.dirs.push(dir);
+            }
+          }
+        }
+      });
+    });
+
+    ui.add_space(6.0);
+    if self.active {
+      ui.button("Stop Watching").clicked().then(Vec::new)
+    } else {
+      ui.add_enabled(!self.dirs.is_empty(), egui::Button::new("Start Watching"))
+        .clicked()
+        .then(|| self.dirs.clone())
+    }
+  }
+
+  /// Shows the activity log, newest last
+  pub fn show_log(&mut self, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+      ui.strong("Activity");
+      if self.active {
+        ui.weak(format!("watching {} folders", self.dirs.len()));
+      }
+      if ui.small_button("Clear").clicked() {
+        self.log.clear();
+      }
+    });
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+    egui::ScrollArea::vertical()
+      .stick_to_bottom(true)
+      .auto_shrink([false, true])
+      .show_rows(ui, row_height, self.log.len(), |ui, rows| {
+        for activity in self.log.range(rows) {
+          ui.horizontal(|ui| {
+            ui.weak(format_time(activity.time));
+            if activity.error {
+              ui.colored_label(ui.visuals().error_fg_color, &activity.message);
+            } else {
+              ui.label(&activity.message);
+            }
+          });
+        }
+      });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::test_dir::TestDir;
+
+  #[test]
+  fn watch_subdirectories() {
+    let dir = TestDir::new("watch");
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    let mut watcher = Watcher::new(&[dir.to_path_buf()]).unwrap();
+    std::fs::write(dir.join("sub").join("Zelda.eep"), [0]).unwrap();
+    let changes = watcher.wait(Duration::from_millis(100)).unwrap();
+    assert!(changes.files.contains(&dir.join("sub").join("Zelda.eep")));
+    assert!(!changes.overflowed);
+  }
+}
@@ -1,16 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
 
-use super::check_can_add_file;
+use super::{
+  byte_order::{Detection, Probe},
+  check_can_add_file,
+  controller_pack::{check_file, CheckedFile},
+  sync,
+  watch::{Watcher, DEBOUNCE},
+};
 
 pub(super) enum Work {
-  ScanDirectory(std::path::PathBuf),
+  ScanDirectory {
+    dir: std::path::PathBuf,
+    recursive: bool,
+  },
+  CheckPacks {
+    files: Vec<std::path::PathBuf>,
+    repair: bool,
+  },
+  Sync(sync::Job),
+  /// Detects the byte order of the groups from their files
+  DetectByteOrder(Vec<(String, Probe)>),
+  /// Watches the directories for changed saves, stopping any previous watch
+  Watch {
+    dirs: Vec<std::path::PathBuf>,
+  },
 }
 
 pub(super) enum WorkResult {
   ScanDirectory(ScanDirResult),
+  CheckPacks(Vec<CheckPackResult>),
+  Sync(sync::Done),
+  DetectByteOrder(Vec<(String, Probe, Option<Detection>)>),
+  Watch(WatchEvent),
 }
 
-pub(super) type ScanDirResult = Result<Vec<std::path::PathBuf>, GenericError>;
+pub(super) enum WatchEvent {
+  Started(Vec<std::path::PathBuf>),
+  Stopped,
+  /// Saves written to the watched directories, once they stopped changing
+  Changed(Vec<std::path::PathBuf>),
+  /// Changes were dropped, so the watched directories were rescanned
+  Overflowed,
+  Failed(GenericError),
+}
+
+pub(super) type ScanDirResult = Result<ScannedDir, GenericError>;
+pub(super) type CheckPackResult = Result<CheckedFile, GenericError>;
+
+pub(super) struct ScannedDir {
+  /// The scanned directory, which the found files are relative to
+  pub root: std::path::PathBuf,
+  pub files: Vec<std::path::PathBuf>,
+}
 
 #[derive(Debug)]
 pub(super) struct GenericError {
@@ -19,7 +62,7 @@ pub(super) struct GenericError {
 }
 
 impl GenericError {
-  fn new(error: std::io::Error, path: std::path::PathBuf) -> Self {
+  pub(super) fn new(error: std::io::Error, path: std::path::PathBuf) -> Self {
     Self { path, error }
   }
 }
@@ -38,22 +81,108 @@ impl std::error::Error for GenericError {}
 
 pub(super) fn start_worker_thread(receiver: Receiver<Work>, result_sender: Sender<WorkResult>) {
   std::thread::spawn(move || {
+    let mut watching: Option<Arc<AtomicBool>> = None;
     for work in receiver.iter() {
       let _ = result_sender.send(match work {
-        Work::ScanDirectory(dir) => WorkResult::ScanDirectory(scan_directory(dir)),
+        Work::ScanDirectory { dir, recursive } => {
+          WorkResult::ScanDirectory(scan_directory(dir, recursive))
+        }
+        Work::CheckPacks { files, repair } => WorkResult::CheckPacks(check_packs(files, repair)),
+        Work::Sync(job) => WorkResult::Sync(job.run()),
+        Work::DetectByteOrder(probes) => WorkResult::DetectByteOrder(
+          probes
+            .into_iter()
+            .map(|(group, probe)| {
+              let detection = probe.detect();
+              (group, probe, detection)
+            })
+            .collect(),
+        ),
+        Work::Watch { dirs } => {
+          if let Some(stop) = watching.take() {
+            stop.store(true, Ordering::Relaxed);
+          }
+          WorkResult::Watch(if dirs.is_empty() {
+            WatchEvent::Stopped
+          } else {
+            match Watcher::new(&dirs) {
+              Ok(watcher) => {
+                let stop = Arc::new(AtomicBool::new(false));
+                watching = Some(stop.clone());
+                let sender = result_sender.clone();
+                std::thread::spawn(move || watch_dirs(watcher, stop, sender));
+                WatchEvent::Started(dirs)
+              }
+              Err(error) => WatchEvent::Failed(error),
+            }
+          })
+        }
       });
     }
   });
 }
 
-fn scan_directory(dir: std::path::PathBuf) -> ScanDirResult {
+/// Sends the saves changed in the watched directories, once no more changes come for a while
+fn watch_dirs(mut watcher: Watcher, stop: Arc<AtomicBool>, sender: Sender<WorkResult>) {
+  let mut pending = std::collections::BTreeSet::new();
+  let mut last_change = std::time::Instant::now();
+  while !stop.load(Ordering::Relaxed) {
+    match watcher.wait(std::time::Duration::from_millis(250)) {
+      Ok(changes) => {
+        if changes.overflowed {
+          let _ = sender.send(WorkResult::Watch(WatchEvent::Overflowed));
+        }
+        let mut changed = changes
+          .files
+          .into_iter()
+          .filter(|p| check_can_add_file(p))
+          .peekable();
+        if changed.peek().is_some() {
+          pending.extend(changed);
+          last_change = std::time::Instant::now();
+        } else if !pending.is_empty() && last_change.elapsed() >= DEBOUNCE {
+          let changed = std::mem::take(&mut pending).into_iter().collect();
+          let _ = sender.send(WorkResult::Watch(WatchEvent::Changed(changed)));
+        }
+      }
+      Err(error) => {
+        let error = GenericError::new(error, std::path::PathBuf::new());
+        let _ = sender.send(WorkResult::Watch(WatchEvent::Failed(error)));
+        return;
+      }
+    }
+  }
+}
+
+fn scan_directory(dir: std::path::PathBuf, recursive: bool) -> ScanDirResult {
   let mut files = Vec::new();
-  for entry in (std::fs::read_dir(&dir).map_err(|e| GenericError::new(e, dir))?).flatten() {
+  scan_files(&dir, recursive, &mut files)?;
+  Ok(ScannedDir { root: dir, files })
+}
+
+fn scan_files(
+  dir: &std::path::Path,
+  recursive: bool,
+  files: &mut Vec<std::path::PathBuf>,
+) -> Result<(), GenericError> {
+  let entries = std::fs::read_dir(dir).map_err(|e| GenericError::new(e, dir.to_path_buf()))?;
+  for entry in entries.flatten() {
     let path = entry.path();
-    if check_can_add_file(&path) {
+    if entry.file_type().is_ok_and(|t| t.is_dir()) {
+      // hidden directories include the conversion staging ones
+      if recursive && !entry.file_name().to_string_lossy().starts_with('.') {
+        scan_files(&path, recursive, files)?;
+      }
+    } else if check_can_add_file(&path) {
       files.push(path)
     }
   }
+  Ok(())
+}
 
-  Ok(files)
+fn check_packs(files: Vec<std::path::PathBuf>, repair: bool) -> Vec<CheckPackResult> {
+  files
+    .into_iter()
+    .map(|file| check_file(file.clone(), repair).map_err(|e| GenericError::new(e, file)))
+    .collect()
 }
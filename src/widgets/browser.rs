@@ -29,7 +29,7 @@ impl<'o> std::fmt::Debug for Browse<'o> {
 #[derive(Debug, Clone)]
 enum Kind<'f> {
   PickFile(&'f [FileFilter<'f>]),
-  // SaveFile(&'f [FileFilter<'f>], Option<&'f str>),
+  SaveFile(&'f [FileFilter<'f>], Option<&'f str>),
   PickDir,
 }
 
@@ -50,9 +50,9 @@ impl<'o> Browse<'o> {
     Self::new("Select Directory...", Kind::PickDir)
   }
 
-  // pub fn save_file<'f>(name: Option<&'f str>, filters: &'f [FileFilter]) -> Browse<'f> {
-  //   Browse::new("Select File...", Kind::SaveFile(filters, name))
-  // }
+  pub fn save_file<'f>(name: Option<&'f str>, filters: &'f [FileFilter]) -> Browse<'f> {
+    Browse::new("Select File...", Kind::SaveFile(filters, name))
+  }
 
   pub fn set_default_text(self, default_text: impl Into<egui::WidgetText>) -> Self {
     Self {
@@ -100,7 +100,7 @@ impl UiBrowser for egui::Ui {
       let mut dialog = rfd::FileDialog::new();
 
       // put filters
-      if let Kind::PickFile(filters) /*| Kind::SaveFile(filters, _) */ = &kind {
+      if let Kind::PickFile(filters) | Kind::SaveFile(filters, _) = &kind {
         for FileFilter { name, extensions } in *filters {
           dialog = dialog.add_filter(*name, extensions)
         }
@@ -122,12 +122,12 @@ impl UiBrowser for egui::Ui {
 
       if let Some(selected_path) = match kind {
         Kind::PickFile(_) => dialog.pick_file(),
-        /*Kind::SaveFile(_, name) => {
+        Kind::SaveFile(_, name) => {
           if let Some(name) = name {
             dialog = dialog.set_file_name(name);
           }
           dialog.save_file()
-        }*/
+        }
         Kind::PickDir => dialog.pick_folder(),
       } {
         path.replace(selected_path);